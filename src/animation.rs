@@ -1,3 +1,117 @@
+use anyhow::{anyhow, ensure, Result};
+
+use std::str::FromStr;
+
+/// How far a spring's oscillation decays before the animation ends.
+const SPRING_SETTLE: f64 = 1e-3;
+
+/// Time (in seconds) to start any movement in [`fitts_time`], regardless of
+/// its distance.
+const FITTS_REACTION: f64 = 0.1;
+
+/// Timing curves mapping animation progress to the fraction of distance
+/// travelled.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum Easing {
+    #[default]
+    EaseInOut,
+    Linear,
+    CubicBezier {
+        x1: f64,
+        y1: f64,
+        x2: f64,
+        y2: f64,
+    },
+    Spring {
+        stiffness: f64,
+        damping: f64,
+    },
+}
+
+impl Easing {
+    /// Returns the fraction of distance travelled at progress `t`.
+    pub fn apply(&self, t: f64) -> f64 {
+        match *self {
+            Easing::EaseInOut => ease_in_out(t),
+            Easing::Linear => t.clamp(0., 1.),
+            Easing::CubicBezier { x1, y1, x2, y2 } => cubic_bezier(x1, y1, x2, y2, t),
+            Easing::Spring { stiffness, damping } => spring(stiffness, damping, t),
+        }
+    }
+}
+
+impl FromStr for Easing {
+    type Err = anyhow::Error;
+
+    /// Parses an easing name, a CSS-style `cubic-bezier(x1,y1,x2,y2)`, or
+    /// `spring(stiffness,damping)`.
+    fn from_str(s: &str) -> Result<Self> {
+        let curve = |x1, y1, x2, y2| Easing::CubicBezier { x1, y1, x2, y2 };
+        let s = s.trim();
+        match s {
+            "ease-in-out" => return Ok(Easing::EaseInOut),
+            "linear" => return Ok(Easing::Linear),
+            // same curves as the CSS keywords
+            "ease" => return Ok(curve(0.25, 0.1, 0.25, 1.)),
+            "ease-in" => return Ok(curve(0.42, 0., 1., 1.)),
+            "ease-out" => return Ok(curve(0., 0., 0.58, 1.)),
+            "spring" => {
+                return Ok(Easing::Spring {
+                    stiffness: 100.,
+                    damping: 10.,
+                })
+            }
+            _ => (),
+        }
+
+        if let Some(args) = function_args(s, "spring") {
+            let values = parse_args(args, "spring")?;
+            let [stiffness, damping] = values[..] else {
+                return Err(anyhow!("spring takes 2 values, found {}", values.len()));
+            };
+            ensure!(
+                stiffness.is_finite() && stiffness > 0. && damping.is_finite() && damping > 0.,
+                "spring stiffness and damping must be positive numbers"
+            );
+
+            return Ok(Easing::Spring { stiffness, damping });
+        }
+
+        let args =
+            function_args(s, "cubic-bezier").ok_or_else(|| anyhow!("unknown easing '{s}'"))?;
+        let values = parse_args(args, "cubic-bezier")?;
+        let [x1, y1, x2, y2] = values[..] else {
+            return Err(anyhow!(
+                "cubic-bezier takes 4 values, found {}",
+                values.len()
+            ));
+        };
+        ensure!(
+            (0. ..=1.).contains(&x1) && (0. ..=1.).contains(&x2),
+            "cubic-bezier x values must be between 0 and 1"
+        );
+        ensure!(
+            y1.is_finite() && y2.is_finite(),
+            "cubic-bezier y values must be finite"
+        );
+
+        Ok(curve(x1, y1, x2, y2))
+    }
+}
+
+/// Returns the arguments of `s` if it is written as `name(args)`.
+fn function_args<'a>(s: &'a str, name: &str) -> Option<&'a str> {
+    s.strip_prefix(name)?.strip_prefix('(')?.strip_suffix(')')
+}
+
+/// Parses comma separated numbers given to the easing function `name`.
+fn parse_args(args: &str, name: &str) -> Result<Vec<f64>> {
+    args.split(',')
+        .map(|v| v.trim().parse::<f64>())
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| anyhow!("invalid {name} value ({e})"))
+}
+
 /// Linearly interpolates a value between `[min, max]`, given a `t` between
 /// `[0, 1]`.
 ///
@@ -19,6 +133,173 @@ pub fn ease_in_out(t: f64) -> f64 {
     lerp(in_t, out_t, t)
 }
 
+/// Evaluates a CSS-style cubic Bézier timing curve from `(0, 0)` to `(1, 1)`
+/// with control points `(x1, y1)` and `(x2, y2)`, returning the curve's `y`
+/// where its `x` equals `t`.
+///
+/// Note that `t` is clamped between `0` and `1` (inclusive).
+pub fn cubic_bezier(x1: f64, y1: f64, x2: f64, y2: f64, t: f64) -> f64 {
+    let t = t.clamp(0., 1.);
+
+    // x is monotonic when x1 and x2 are in [0, 1], so Newton's method
+    // converges quickly from `t` in almost all cases
+    let mut u = t;
+    for _ in 0..8 {
+        let err = bezier(x1, x2, u) - t;
+        if err.abs() < 1e-6 {
+            return bezier(y1, y2, u);
+        }
+        let slope = bezier_slope(x1, x2, u);
+        if slope.abs() < 1e-6 {
+            break;
+        }
+        u = (u - err / slope).clamp(0., 1.);
+    }
+
+    // fall back to bisection for flat sections of the curve
+    let (mut lo, mut hi) = (0., 1.);
+    u = t;
+    for _ in 0..32 {
+        if bezier(x1, x2, u) < t {
+            lo = u;
+        } else {
+            hi = u;
+        }
+        u = (lo + hi) / 2.;
+    }
+
+    bezier(y1, y2, u)
+}
+
+/// Returns the position of a unit mass on a damped spring pulled from `0` to
+/// `1`, which may overshoot `1` before settling.
+///
+/// `t` between `[0, 1]` is stretched over the time it takes the spring to
+/// settle, so the animation always ends at rest on `1`.
+pub fn spring(stiffness: f64, damping: f64, t: f64) -> f64 {
+    if t >= 1. {
+        return 1.;
+    }
+    let t = t.max(0.);
+
+    let omega = stiffness.sqrt();
+    let zeta = damping / (2. * omega);
+    if zeta >= 1. {
+        // critically damped (or slower), which never overshoots
+        let time = t * -SPRING_SETTLE.ln() * 2. / omega;
+        return 1. - (-omega * time).exp() * (1. + omega * time);
+    }
+
+    let decay = zeta * omega;
+    let omega_d = omega * (1. - square(zeta)).sqrt();
+    let time = t * -SPRING_SETTLE.ln() / decay;
+
+    1. - (-decay * time).exp() * ((omega_d * time).cos() + decay / omega_d * (omega_d * time).sin())
+}
+
+/// Returns smooth 1D gradient (Perlin) noise at `x`, roughly between `-1` and
+/// `1`. Different seeds give unrelated noise.
+pub fn noise(x: f64, seed: u64) -> f64 {
+    let i = x.floor();
+    let f = x - i;
+    let g0 = gradient(i as i64, seed) * f;
+    let g1 = gradient(i as i64 + 1, seed) * (f - 1.);
+
+    // 1D gradient noise peaks at 0.5, so scale it to fill [-1, 1]
+    2. * lerp(g0, g1, fade(f))
+}
+
+/// Evaluates a cubic Hermite spline between `p0` and `p1` at `t`, where `m0`
+/// and `m1` are the tangents (velocities) at each end.
+///
+/// Note that `t` is clamped between `0` and `1` (inclusive).
+#[inline]
+pub fn hermite(p0: f64, m0: f64, p1: f64, m1: f64, t: f64) -> f64 {
+    let t = t.clamp(0., 1.);
+    let t2 = square(t);
+    let t3 = t2 * t;
+
+    (2. * t3 - 3. * t2 + 1.) * p0
+        + (t3 - 2. * t2 + t) * m0
+        + (-2. * t3 + 3. * t2) * p1
+        + (t3 - t2) * m1
+}
+
+/// Returns the time (in seconds) a person takes to point at a target `width`
+/// pixels wide from `distance` away according to Fitts' law, where
+/// `difficulty` is the seconds taken per bit of the index of difficulty.
+#[inline]
+pub fn fitts_time(distance: f64, width: f64, difficulty: f64) -> f64 {
+    FITTS_REACTION + difficulty * (distance / width + 1.).log2()
+}
+
+/// Returns the shortest time (in seconds) to travel `distance` from rest to
+/// rest when acceleration is limited to `accel`.
+#[inline]
+pub fn min_travel_time(distance: f64, accel: f64) -> f64 {
+    2. * (distance / accel).sqrt()
+}
+
+/// Returns the fraction of `distance` travelled at time `t` (in seconds) for
+/// a trapezoidal velocity profile: constant acceleration `accel` up to a
+/// cruise speed, then constant deceleration to arrive at rest at `duration`.
+///
+/// `duration` must be at least [`min_travel_time`], otherwise the profile
+/// degrades to the triangular one. Note that `t` is clamped between `0` and
+/// `duration` (inclusive).
+pub fn trapezoid(distance: f64, accel: f64, duration: f64, t: f64) -> f64 {
+    if distance <= 0. || duration <= 0. {
+        return 1.;
+    }
+
+    let t = t.clamp(0., duration);
+    let discriminant = (square(accel * duration) - 4. * accel * distance).max(0.);
+    let cruise = (accel * duration - discriminant.sqrt()) / 2.;
+    let t_accel = cruise / accel;
+
+    let travelled = if t < t_accel {
+        0.5 * accel * square(t)
+    } else if t < duration - t_accel {
+        0.5 * accel * square(t_accel) + cruise * (t - t_accel)
+    } else {
+        distance - 0.5 * accel * square(duration - t)
+    };
+
+    (travelled / distance).clamp(0., 1.)
+}
+
+/// Evaluates one axis of a cubic Bézier curve from `0` to `1` with control
+/// values `a` and `b`.
+#[inline]
+fn bezier(a: f64, b: f64, u: f64) -> f64 {
+    3. * square(flip(u)) * u * a + 3. * flip(u) * square(u) * b + square(u) * u
+}
+
+/// Returns the derivative of [`bezier`] with respect to `u`.
+#[inline]
+fn bezier_slope(a: f64, b: f64, u: f64) -> f64 {
+    3. * square(flip(u)) * a + 6. * flip(u) * u * (b - a) + 3. * square(u) * flip(b)
+}
+
+/// Returns a pseudo-random gradient between `-1` and `1` for lattice point `i`.
+fn gradient(i: i64, seed: u64) -> f64 {
+    // splitmix64 finalizer
+    let mut h = (i as u64)
+        .wrapping_add(seed)
+        .wrapping_mul(0x9E37_79B9_7F4A_7C15);
+    h = (h ^ (h >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    h = (h ^ (h >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    h ^= h >> 31;
+
+    (h >> 11) as f64 / (1u64 << 53) as f64 * 2. - 1.
+}
+
+/// Perlin's smootherstep, which eases noise between lattice points.
+#[inline]
+fn fade(t: f64) -> f64 {
+    t * t * t * (t * (t * 6. - 15.) + 10.)
+}
+
 #[inline]
 fn ease_in(t: f64) -> f64 {
     square(t)
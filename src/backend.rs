@@ -0,0 +1,426 @@
+use crate::desktop;
+use crate::mouse::PointExt;
+
+use anyhow::{bail, ensure, Result};
+use clap::builder::PossibleValue;
+use clap::ValueEnum;
+use mouse_rs::Mouse;
+use serde::Deserialize;
+
+use std::error::Error;
+
+/// Reads and moves the system cursor.
+pub trait MouseBackend {
+    /// Returns where the cursor is.
+    fn pos(&self) -> Result<PointExt, Box<dyn Error>>;
+
+    /// Moves the cursor to `p`.
+    fn move_to(&self, p: PointExt) -> Result<(), Box<dyn Error>>;
+}
+
+/// The way cursor movements reach the system.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Backend {
+    /// Sets the cursor position through `mouse-rs`.
+    #[default]
+    MouseRs,
+    /// Sends relative motion events through `SendInput` (Windows only).
+    SendInput,
+    /// Sends relative motion events from a virtual uinput device (Linux only).
+    Uinput,
+    /// Posts mouse moved events through Core Graphics (macOS only).
+    #[serde(rename = "cgevent")]
+    CgEvent,
+    /// Fakes pointer motion through the XTest extension (X11 only).
+    #[serde(rename = "xtest")]
+    XTest,
+}
+
+impl Backend {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Backend::MouseRs => "mouse-rs",
+            Backend::SendInput => "send-input",
+            Backend::Uinput => "uinput",
+            Backend::CgEvent => "cgevent",
+            Backend::XTest => "xtest",
+        }
+    }
+
+    /// Returns `true` if the backend exists on this platform, although it may
+    /// still fail to start (e.g., without permissions).
+    pub fn is_available(&self) -> bool {
+        match self {
+            Backend::MouseRs => true,
+            Backend::SendInput => cfg!(windows),
+            Backend::Uinput => cfg!(target_os = "linux"),
+            Backend::CgEvent => cfg!(target_os = "macos"),
+            Backend::XTest => cfg!(all(unix, not(target_os = "macos"))),
+        }
+    }
+
+    /// Returns `true` if the backend reads the real cursor position back from
+    /// the system. uinput only tracks where it moved the cursor, so it can't
+    /// tell whether a movement arrived.
+    pub fn reads_position(&self) -> bool {
+        !matches!(self, Backend::Uinput)
+    }
+
+    /// Connects to the backend, failing if it isn't usable here.
+    pub fn create(self) -> Result<Box<dyn MouseBackend>> {
+        // macOS silently drops events from untrusted apps, so explain up front
+        ensure!(
+            desktop::is_input_trusted(),
+            "this terminal isn't allowed to control the mouse. Open System Settings > \
+             Privacy & Security > Accessibility, turn on the app running mouser (or add it \
+             with '+'), then restart it"
+        );
+
+        Ok(match self {
+            Backend::MouseRs => Box::new(MouseRs(Mouse::new())),
+            #[cfg(windows)]
+            Backend::SendInput => Box::new(SendInputMouse),
+            #[cfg(not(windows))]
+            Backend::SendInput => bail!("send-input is only available on Windows"),
+            #[cfg(target_os = "linux")]
+            Backend::Uinput => Box::new(uinput::UinputMouse::new()?),
+            #[cfg(not(target_os = "linux"))]
+            Backend::Uinput => bail!("uinput is only available on Linux"),
+            #[cfg(target_os = "macos")]
+            Backend::CgEvent => Box::new(cgevent::CgEventMouse::new()?),
+            #[cfg(not(target_os = "macos"))]
+            Backend::CgEvent => bail!("cgevent is only available on macOS"),
+            #[cfg(all(unix, not(target_os = "macos")))]
+            Backend::XTest => Box::new(xtest::XTestMouse::new()?),
+            #[cfg(not(all(unix, not(target_os = "macos"))))]
+            Backend::XTest => bail!("xtest is only available with X11"),
+        })
+    }
+}
+
+impl ValueEnum for Backend {
+    fn value_variants<'a>() -> &'a [Self] {
+        &[
+            Backend::MouseRs,
+            Backend::SendInput,
+            Backend::Uinput,
+            Backend::CgEvent,
+            Backend::XTest,
+        ]
+    }
+
+    fn to_possible_value(&self) -> Option<PossibleValue> {
+        Some(match self {
+            Backend::MouseRs => PossibleValue::new("mouse-rs").help("Set the cursor position"),
+            Backend::SendInput => {
+                PossibleValue::new("send-input").help("Send relative motion events (Windows)")
+            }
+            Backend::Uinput => {
+                PossibleValue::new("uinput").help("Send relative motion events (Linux, Wayland)")
+            }
+            Backend::CgEvent => {
+                PossibleValue::new("cgevent").help("Post mouse moved events (macOS)")
+            }
+            Backend::XTest => PossibleValue::new("xtest").help("Fake pointer motion (X11)"),
+        })
+    }
+}
+
+/// Places the cursor directly, which is the most portable but looks like a
+/// warp rather than motion to some applications.
+struct MouseRs(Mouse);
+
+impl MouseBackend for MouseRs {
+    fn pos(&self) -> Result<PointExt, Box<dyn Error>> {
+        Ok(self.0.get_position()?.into())
+    }
+
+    fn move_to(&self, p: PointExt) -> Result<(), Box<dyn Error>> {
+        self.0.move_to(p.x, p.y)
+    }
+}
+
+/// Moves the cursor with relative motion events, which applications see as
+/// a real mouse moving rather than the cursor being placed somewhere.
+#[cfg(windows)]
+struct SendInputMouse;
+
+#[cfg(windows)]
+impl MouseBackend for SendInputMouse {
+    fn pos(&self) -> Result<PointExt, Box<dyn Error>> {
+        use windows_sys::Win32::Foundation::POINT;
+        use windows_sys::Win32::UI::WindowsAndMessaging::GetCursorPos;
+
+        let mut p = POINT { x: 0, y: 0 };
+        // SAFETY: the point is only read if the call succeeded
+        if unsafe { GetCursorPos(&mut p) } == 0 {
+            return Err(std::io::Error::last_os_error().into());
+        }
+
+        Ok(PointExt::new(p.x, p.y))
+    }
+
+    fn move_to(&self, p: PointExt) -> Result<(), Box<dyn Error>> {
+        use windows_sys::Win32::UI::Input::KeyboardAndMouse::{
+            SendInput, INPUT, INPUT_0, INPUT_MOUSE, MOUSEEVENTF_MOVE, MOUSEINPUT,
+        };
+
+        // pointer acceleration can scale relative motion, so always aim from
+        // where the cursor really is and let the next move make up the error
+        let start = self.pos()?;
+        let (dx, dy) = (p.x - start.x, p.y - start.y);
+        if dx == 0 && dy == 0 {
+            return Ok(());
+        }
+
+        let input = INPUT {
+            r#type: INPUT_MOUSE,
+            Anonymous: INPUT_0 {
+                mi: MOUSEINPUT {
+                    dx,
+                    dy,
+                    mouseData: 0,
+                    dwFlags: MOUSEEVENTF_MOVE,
+                    time: 0,
+                    dwExtraInfo: 0,
+                },
+            },
+        };
+        // SAFETY: the input is fully initialized and outlives the call
+        let sent = unsafe { SendInput(1, &input, std::mem::size_of::<INPUT>() as i32) };
+        if sent != 1 {
+            return Err(std::io::Error::last_os_error().into());
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(target_os = "linux")]
+pub use uinput::check_access as check_uinput;
+
+#[cfg(target_os = "linux")]
+mod uinput {
+    use super::MouseBackend;
+    use crate::mouse::PointExt;
+
+    use anyhow::{anyhow, Result};
+    use evdev::uinput::{VirtualDevice, VirtualDeviceBuilder};
+    use evdev::{AttributeSet, EventType, InputEvent, Key, RelativeAxisType};
+    use mouse_rs::Mouse;
+
+    use std::cell::{Cell, RefCell};
+    use std::error::Error;
+    use std::fs::OpenOptions;
+    use std::io::{self, ErrorKind};
+    use std::time::Duration;
+
+    const DEVICE_NAME: &str = "mouser virtual pointer";
+    const DEVICE_PATH: &str = "/dev/uinput";
+    /// How long to give the compositor to pick up a new device, since events
+    /// sent before then are lost.
+    const SETTLE_TIME: Duration = Duration::from_millis(250);
+
+    /// Moves the cursor with a virtual pointer device, which works anywhere
+    /// the kernel delivers input (Wayland compositors, X11, and consoles).
+    ///
+    /// Devices can't report where the cursor is, so the position is tracked
+    /// from the movements sent instead, starting from the X11 cursor when
+    /// there is one. The device is removed again when this is dropped.
+    pub struct UinputMouse {
+        device: RefCell<VirtualDevice>,
+        pos: Cell<PointExt>,
+    }
+
+    impl UinputMouse {
+        pub fn new() -> Result<Self> {
+            let mut keys = AttributeSet::<Key>::new();
+            // without a button, compositors don't treat the device as a pointer
+            keys.insert(Key::BTN_LEFT);
+            let mut axes = AttributeSet::<RelativeAxisType>::new();
+            axes.insert(RelativeAxisType::REL_X);
+            axes.insert(RelativeAxisType::REL_Y);
+
+            let device = VirtualDeviceBuilder::new()
+                .and_then(|b| b.name(DEVICE_NAME).with_keys(&keys))
+                .and_then(|b| b.with_relative_axes(&axes))
+                .and_then(|b| b.build())
+                .map_err(access_error)?;
+            spin_sleep::sleep(SETTLE_TIME);
+
+            Ok(Self {
+                device: RefCell::new(device),
+                pos: Cell::new(x11_position().unwrap_or(PointExt::new(0, 0))),
+            })
+        }
+    }
+
+    impl MouseBackend for UinputMouse {
+        fn pos(&self) -> Result<PointExt, Box<dyn Error>> {
+            Ok(self.pos.get())
+        }
+
+        fn move_to(&self, p: PointExt) -> Result<(), Box<dyn Error>> {
+            let start = self.pos.get();
+            let (dx, dy) = (p.x - start.x, p.y - start.y);
+            if dx == 0 && dy == 0 {
+                return Ok(());
+            }
+
+            // a sync report is appended, so both axes arrive as one motion
+            self.device.borrow_mut().emit(&[
+                InputEvent::new(EventType::RELATIVE, RelativeAxisType::REL_X.0, dx),
+                InputEvent::new(EventType::RELATIVE, RelativeAxisType::REL_Y.0, dy),
+            ])?;
+            self.pos.set(p);
+
+            Ok(())
+        }
+    }
+
+    /// Checks that a virtual pointer could be created, without creating one.
+    pub fn check_access() -> Result<()> {
+        OpenOptions::new()
+            .write(true)
+            .open(DEVICE_PATH)
+            .map_err(access_error)?;
+
+        Ok(())
+    }
+
+    /// Explains how to fix a failure to use `/dev/uinput`.
+    fn access_error(e: io::Error) -> anyhow::Error {
+        match e.kind() {
+            ErrorKind::PermissionDenied => anyhow!(
+                "no permission to write to {DEVICE_PATH}, try adding a udev rule like \
+                 'KERNEL==\"uinput\", GROUP=\"input\", MODE=\"0660\"' and joining the \
+                 input group"
+            ),
+            ErrorKind::NotFound => {
+                anyhow!("{DEVICE_PATH} doesn't exist, try loading it with 'modprobe uinput'")
+            }
+            _ => anyhow!("failed to create a virtual pointer ({e})"),
+        }
+    }
+
+    /// Returns the cursor position from X11, if there is a display to ask.
+    fn x11_position() -> Option<PointExt> {
+        std::env::var_os("DISPLAY")?;
+        Mouse::new().get_position().ok().map(PointExt::from)
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod cgevent {
+    use super::MouseBackend;
+    use crate::mouse::PointExt;
+
+    use anyhow::{anyhow, Result};
+    use core_graphics::event::{CGEvent, CGEventTapLocation, CGEventType, CGMouseButton};
+    use core_graphics::event_source::{CGEventSource, CGEventSourceStateID};
+    use core_graphics::geometry::CGPoint;
+
+    use std::error::Error;
+
+    /// Moves the cursor by posting the same events a real mouse produces,
+    /// which applications and idle timers can't tell apart from the user.
+    pub struct CgEventMouse {
+        source: CGEventSource,
+    }
+
+    impl CgEventMouse {
+        pub fn new() -> Result<Self> {
+            let source = CGEventSource::new(CGEventSourceStateID::HIDSystemState)
+                .map_err(|_| anyhow!("failed to create a Core Graphics event source"))?;
+
+            Ok(Self { source })
+        }
+    }
+
+    impl MouseBackend for CgEventMouse {
+        fn pos(&self) -> Result<PointExt, Box<dyn Error>> {
+            let event = CGEvent::new(self.source.clone())
+                .map_err(|_| "failed to create an event to read the cursor")?;
+            let p = event.location();
+
+            Ok(PointExt::new(p.x.round() as i32, p.y.round() as i32))
+        }
+
+        fn move_to(&self, p: PointExt) -> Result<(), Box<dyn Error>> {
+            let event = CGEvent::new_mouse_event(
+                self.source.clone(),
+                CGEventType::MouseMoved,
+                CGPoint::new(p.x as f64, p.y as f64),
+                CGMouseButton::Left,
+            )
+            .map_err(|_| "failed to create a mouse moved event")?;
+            event.post(CGEventTapLocation::HID);
+
+            Ok(())
+        }
+    }
+}
+
+#[cfg(all(unix, not(target_os = "macos")))]
+mod xtest {
+    use super::MouseBackend;
+    use crate::mouse::PointExt;
+
+    use anyhow::{anyhow, ensure, Result};
+    use x11rb::connection::Connection;
+    use x11rb::protocol::xproto::{ConnectionExt as _, Window, MOTION_NOTIFY_EVENT};
+    use x11rb::protocol::xtest::{self, ConnectionExt as _};
+    use x11rb::rust_connection::RustConnection;
+
+    use std::error::Error;
+
+    /// Moves the cursor with fake motion events from XTest, which the X
+    /// server handles like real input (unlike a warp), so idle monitors that
+    /// only watch for input events are reset too.
+    pub struct XTestMouse {
+        conn: RustConnection,
+        root: Window,
+    }
+
+    impl XTestMouse {
+        pub fn new() -> Result<Self> {
+            let (conn, screen) = x11rb::connect(None)
+                .map_err(|e| anyhow!("failed to connect to the X server ({e})"))?;
+            ensure!(
+                conn.extension_information(xtest::X11_EXTENSION_NAME)?
+                    .is_some(),
+                "the X server doesn't support the XTest extension"
+            );
+            let root = conn.setup().roots[screen].root;
+
+            Ok(Self { conn, root })
+        }
+    }
+
+    impl MouseBackend for XTestMouse {
+        fn pos(&self) -> Result<PointExt, Box<dyn Error>> {
+            let reply = self.conn.query_pointer(self.root)?.reply()?;
+
+            Ok(PointExt::new(reply.root_x.into(), reply.root_y.into()))
+        }
+
+        fn move_to(&self, p: PointExt) -> Result<(), Box<dyn Error>> {
+            let clamp = |v: i32| v.clamp(i16::MIN.into(), i16::MAX.into()) as i16;
+
+            // a detail of 0 makes the motion absolute, relative to `root`
+            self.conn.xtest_fake_input(
+                MOTION_NOTIFY_EVENT,
+                0,
+                x11rb::CURRENT_TIME,
+                self.root,
+                clamp(p.x),
+                clamp(p.y),
+                0,
+            )?;
+            self.conn.flush()?;
+
+            Ok(())
+        }
+    }
+}
@@ -1,16 +1,163 @@
-use clap::ArgMatches;
+use crate::display::{Display, Units};
+use crate::mouse::PointExt;
+use crate::window;
 
-#[derive(Debug, Clone)]
+use anyhow::{anyhow, bail, ensure, Result};
+use clap::builder::PossibleValue;
+use clap::{ArgMatches, ValueEnum};
+use serde::Deserialize;
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(try_from = "BoundsValue")]
 pub enum Bounds {
-    Rect { x1: i32, y1: i32, x2: i32, y2: i32 },
-    Relative { dx: i32, dy: i32 },
+    Rect {
+        x1: i32,
+        y1: i32,
+        x2: i32,
+        y2: i32,
+    },
+    Relative {
+        dx: i32,
+        dy: i32,
+    },
+    /// Absolute rectangles with weights, one of which is picked for each
+    /// movement (see [`Bounds::choose`]).
+    Regions(Vec<(Region, u32)>),
+    /// The focused window, shrunk by `inset` pixels on each side. It is
+    /// looked up again for each movement (see [`Bounds::choose`]).
+    ActiveWindow {
+        inset: u32,
+    },
+    /// A rectangle `width` by `height` in the middle of a display, which is
+    /// made absolute at startup (see [`Bounds::center_on`]).
+    Center {
+        width: u32,
+        height: u32,
+    },
+    /// The first window with `title` in its title or class, shrunk by
+    /// `inset` pixels on each side. It is looked up again for each movement
+    /// (see [`Bounds::choose`]).
+    Window {
+        title: String,
+        inset: u32,
+    },
 }
 
 impl Bounds {
     pub fn is_relative(&self) -> bool {
         match self {
-            Bounds::Rect { .. } => false,
-            Bounds::Relative { .. } => true,
+            Bounds::Rect { .. } | Bounds::Regions(_) | Bounds::Center { .. } => false,
+            // windows move around, so they can't be checked up front either
+            Bounds::Relative { .. } | Bounds::ActiveWindow { .. } | Bounds::Window { .. } => true,
+        }
+    }
+
+    /// Returns the bounds to use for a single movement, which picks one of
+    /// several regions at random by weight, or finds where a window is now.
+    ///
+    /// Fails with [`window::NotFound`] if no window matches the title.
+    pub fn choose(&self, rng: &fastrand::Rng) -> Result<Bounds> {
+        match self {
+            Bounds::Regions(regions) => {
+                let total: u32 = regions.iter().map(|(_, weight)| weight).sum();
+                let mut pick = rng.u32(0..total);
+                for (region, weight) in regions {
+                    if pick < *weight {
+                        return Ok(Bounds::from(*region));
+                    }
+                    pick -= weight;
+                }
+
+                unreachable!("pick should be less than the total weight")
+            }
+            Bounds::ActiveWindow { inset } => window::active()
+                .map(|region| Bounds::from(region.inset(*inset)))
+                .map_err(|e| anyhow!("failed to find the active window ({e})")),
+            Bounds::Window { title, inset } => match window::find(title) {
+                Ok(Some(region)) => Ok(Bounds::from(region.inset(*inset))),
+                Ok(None) => Err(window::NotFound(title.clone()).into()),
+                Err(e) => bail!("failed to find window '{title}' ({e})"),
+            },
+            _ => Ok(self.clone()),
+        }
+    }
+
+    /// Sets how far movements stay from the edges of a window.
+    pub fn with_inset(self, inset: u32) -> Result<Self> {
+        match self {
+            Bounds::ActiveWindow { .. } => Ok(Bounds::ActiveWindow { inset }),
+            Bounds::Window { title, .. } => Ok(Bounds::Window { title, inset }),
+            _ => bail!("an inset can only be used with window bounds"),
+        }
+    }
+
+    /// Sets the weight of each region, in the same order they were given.
+    pub fn with_weights(self, weights: &[u32]) -> Result<Self> {
+        let Bounds::Regions(regions) = self else {
+            bail!("weights can only be used with several absolute bounds");
+        };
+        ensure!(
+            weights.len() == regions.len(),
+            "expected {} weights (one per region), found {}",
+            regions.len(),
+            weights.len()
+        );
+        ensure!(weights.iter().all(|w| *w > 0), "weights must be positive");
+
+        Ok(Bounds::Regions(
+            regions
+                .into_iter()
+                .zip(weights)
+                .map(|((region, _), weight)| (region, *weight))
+                .collect(),
+        ))
+    }
+
+    /// Returns centered bounds as an absolute rectangle in the middle of
+    /// `display`, with the size measured in `units`. Other bounds are
+    /// returned as they are.
+    pub fn center_on(&self, display: &Display, units: Units) -> Self {
+        let Bounds::Center { width, height } = *self else {
+            return self.clone();
+        };
+
+        let factor = display.factor(units);
+        let width = (width as f64 * factor).round() as i32;
+        let height = (height as f64 * factor).round() as i32;
+        let x1 = display.x + (display.width as i32 - width) / 2;
+        let y1 = display.y + (display.height as i32 - height) / 2;
+
+        Bounds::Rect {
+            x1,
+            y1,
+            x2: x1 + width - 1,
+            y2: y1 + height - 1,
+        }
+    }
+
+    /// Returns these bounds with each corner moved by `f`, which only
+    /// affects absolute bounds.
+    pub fn map(&self, f: impl Fn(PointExt) -> PointExt) -> Self {
+        match *self {
+            Bounds::Rect { x1, y1, x2, y2 } => {
+                let (p1, p2) = (f(PointExt::new(x1, y1)), f(PointExt::new(x2, y2)));
+                Bounds::Rect {
+                    x1: p1.x,
+                    y1: p1.y,
+                    x2: p2.x,
+                    y2: p2.y,
+                }
+            }
+            Bounds::Relative { .. }
+            | Bounds::Center { .. }
+            | Bounds::ActiveWindow { .. }
+            | Bounds::Window { .. } => self.clone(),
+            Bounds::Regions(ref regions) => Bounds::Regions(
+                regions
+                    .iter()
+                    .map(|(region, weight)| (region.map(&f), *weight))
+                    .collect(),
+            ),
         }
     }
 
@@ -18,6 +165,369 @@ impl Bounds {
         match self {
             Bounds::Rect { x1, y1, x2, y2 } => x1 == x2 && y1 == y2,
             Bounds::Relative { dx, dy } => *dx == 0 && *dy == 0,
+            Bounds::Regions(regions) => regions.iter().all(|(r, _)| r.x1 == r.x2 && r.y1 == r.y2),
+            Bounds::Center { width, height } => *width <= 1 && *height <= 1,
+            Bounds::ActiveWindow { .. } | Bounds::Window { .. } => false,
+        }
+    }
+}
+
+/// A rectangle of pixels with `(x1, y1)` top left of `(x2, y2)`, inclusive.
+///
+/// In config files, regions are written as `[x1, y1, x2, y2]` in any corner
+/// order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(from = "[i32; 4]")]
+pub struct Region {
+    pub x1: i32,
+    pub y1: i32,
+    pub x2: i32,
+    pub y2: i32,
+}
+
+impl Region {
+    pub fn contains(&self, p: PointExt) -> bool {
+        (self.x1..=self.x2).contains(&p.x) && (self.y1..=self.y2).contains(&p.y)
+    }
+
+    /// Returns the point in this region nearest to `p`.
+    pub fn clamp(&self, p: PointExt) -> PointExt {
+        PointExt::new(p.x.clamp(self.x1, self.x2), p.y.clamp(self.y1, self.y2))
+    }
+
+    /// Returns this region with each corner moved by `f`.
+    pub fn map(&self, f: impl Fn(PointExt) -> PointExt) -> Region {
+        let (p1, p2) = (
+            f(PointExt::new(self.x1, self.y1)),
+            f(PointExt::new(self.x2, self.y2)),
+        );
+
+        Region::from([p1.x, p1.y, p2.x, p2.y])
+    }
+
+    /// Returns this region shrunk by `px` on each side, or just its middle if
+    /// it is too small for that.
+    pub fn inset(&self, px: u32) -> Region {
+        let px = px as i32;
+        let (cx, cy) = ((self.x1 + self.x2) / 2, (self.y1 + self.y2) / 2);
+
+        Region {
+            x1: (self.x1 + px).min(cx),
+            y1: (self.y1 + px).min(cy),
+            x2: (self.x2 - px).max(cx),
+            y2: (self.y2 - px).max(cy),
+        }
+    }
+
+    pub fn overlaps(&self, other: &Region) -> bool {
+        self.x1 <= other.x2 && other.x1 <= self.x2 && self.y1 <= other.y2 && other.y1 <= self.y2
+    }
+
+    /// Returns the part of this region inside `other`. If they don't overlap
+    /// on an axis, the edge of `other` nearest to this region is used.
+    pub fn intersect(&self, other: &Region) -> Region {
+        let (x1, x2) = overlap(self.x1, self.x2, other.x1, other.x2);
+        let (y1, y2) = overlap(self.y1, self.y2, other.y1, other.y2);
+
+        Region { x1, y1, x2, y2 }
+    }
+
+    /// Returns `p` moved to the nearest multiple of `grid` pixels on each
+    /// axis, staying inside the region where possible.
+    pub fn snap(&self, p: PointExt, grid: u32) -> PointExt {
+        PointExt::new(
+            snap_axis(p.x, self.x1, self.x2, grid as i32),
+            snap_axis(p.y, self.y1, self.y2, grid as i32),
+        )
+    }
+
+    /// Returns `true` if every pixel of this region is inside `zones`.
+    pub fn is_covered_by(&self, zones: &[Region]) -> bool {
+        self.uncovered(zones).is_empty()
+    }
+
+    /// Returns a random point in this region outside all of `zones`, with
+    /// every such point equally likely, or `None` if they cover it.
+    pub fn free_point(&self, zones: &[Region], rng: &fastrand::Rng) -> Option<PointExt> {
+        let cells = self.uncovered(zones);
+        let area = |r: &Region| (r.x2 - r.x1 + 1) as u64 * (r.y2 - r.y1 + 1) as u64;
+        let total: u64 = cells.iter().map(area).sum();
+        if total == 0 {
+            return None;
+        }
+
+        let mut pick = rng.u64(0..total);
+        for cell in &cells {
+            if pick < area(cell) {
+                return Some(PointExt::new(
+                    rng.i32(cell.x1..=cell.x2),
+                    rng.i32(cell.y1..=cell.y2),
+                ));
+            }
+            pick -= area(cell);
+        }
+
+        unreachable!("pick should be less than the total area")
+    }
+
+    /// Splits this region on the edges of `zones` and returns the pieces that
+    /// aren't inside any of them.
+    fn uncovered(&self, zones: &[Region]) -> Vec<Region> {
+        let xs = breaks(self.x1, self.x2, zones.iter().map(|z| (z.x1, z.x2)));
+        let ys = breaks(self.y1, self.y2, zones.iter().map(|z| (z.y1, z.y2)));
+
+        let mut cells = vec![];
+        for x in xs.windows(2) {
+            for y in ys.windows(2) {
+                // each piece is either entirely inside a zone or entirely out
+                let corner = PointExt::new(x[0], y[0]);
+                if !zones.iter().any(|z| z.contains(corner)) {
+                    cells.push(Region {
+                        x1: x[0],
+                        y1: y[0],
+                        x2: x[1] - 1,
+                        y2: y[1] - 1,
+                    });
+                }
+            }
+        }
+
+        cells
+    }
+}
+
+impl From<[i32; 4]> for Region {
+    fn from([x1, y1, x2, y2]: [i32; 4]) -> Self {
+        Region {
+            x1: x1.min(x2),
+            y1: y1.min(y2),
+            x2: x1.max(x2),
+            y2: y1.max(y2),
+        }
+    }
+}
+
+/// Returns the sorted start of every span `[lo, hi]` is split into by the
+/// edges of `spans`, followed by `hi + 1`.
+fn breaks(lo: i32, hi: i32, spans: impl Iterator<Item = (i32, i32)>) -> Vec<i32> {
+    let mut result = vec![lo, hi + 1];
+    for (start, end) in spans {
+        result.extend([start, end + 1].into_iter().filter(|v| *v > lo && *v <= hi));
+    }
+    result.sort_unstable();
+    result.dedup();
+    result
+}
+
+/// An axis that can be locked so the cursor only moves along the other one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Axis {
+    X,
+    Y,
+}
+
+impl Axis {
+    /// Returns `region` narrowed to a single column (for `X`) or row (for
+    /// `Y`) through `p`, or the nearest one inside the region.
+    pub fn lock(&self, region: &Region, p: PointExt) -> Region {
+        let p = region.clamp(p);
+        match self {
+            Axis::X => Region {
+                x1: p.x,
+                x2: p.x,
+                ..*region
+            },
+            Axis::Y => Region {
+                y1: p.y,
+                y2: p.y,
+                ..*region
+            },
+        }
+    }
+}
+
+impl ValueEnum for Axis {
+    fn value_variants<'a>() -> &'a [Self] {
+        &[Axis::X, Axis::Y]
+    }
+
+    fn to_possible_value(&self) -> Option<PossibleValue> {
+        Some(match self {
+            Axis::X => PossibleValue::new("x").help("Keep X fixed, moving vertically"),
+            Axis::Y => PossibleValue::new("y").help("Keep Y fixed, moving horizontally"),
+        })
+    }
+}
+
+/// Returns the overlap of `[a1, a2]` and `[b1, b2]`, or the end of `b` nearest
+/// to `a` if they don't overlap.
+fn overlap(a1: i32, a2: i32, b1: i32, b2: i32) -> (i32, i32) {
+    let (lo, hi) = (a1.max(b1), a2.min(b2));
+    if lo <= hi {
+        (lo, hi)
+    } else if a2 < b1 {
+        (b1, b1)
+    } else {
+        (b2, b2)
+    }
+}
+
+/// Snaps `v` to a multiple of `grid` between `[lo, hi]`, or leaves it as is if
+/// there is no multiple nearby in that range.
+fn snap_axis(v: i32, lo: i32, hi: i32, grid: i32) -> i32 {
+    let snapped = (v as f64 / grid as f64).round() as i32 * grid;
+    [snapped, snapped - grid, snapped + grid]
+        .into_iter()
+        .find(|s| (lo..=hi).contains(s))
+        .unwrap_or(v)
+}
+
+impl Bounds {
+    /// Returns the region movements can reach, where relative bounds are
+    /// centered on `orig` and kept on `screen` if given.
+    ///
+    /// If `orig` isn't on `screen`, relative bounds are centered on the
+    /// screen instead. Several regions give the rectangle covering all of
+    /// them, so use [`Bounds::choose`] first to pick one.
+    ///
+    /// # Panics
+    ///
+    /// Panics for window bounds, which have to be looked up with
+    /// [`Bounds::choose`] first, and for centered bounds, which have to be
+    /// made absolute with [`Bounds::center_on`].
+    pub fn region(&self, orig: PointExt, screen: Option<&Display>) -> Region {
+        match *self {
+            Bounds::ActiveWindow { .. } | Bounds::Window { .. } => {
+                unreachable!("window bounds should be resolved with `choose`")
+            }
+            Bounds::Center { .. } => {
+                unreachable!("centered bounds should be resolved with `center_on`")
+            }
+            Bounds::Regions(ref regions) => Region {
+                x1: regions.iter().map(|(r, _)| r.x1).min().unwrap_or(0),
+                y1: regions.iter().map(|(r, _)| r.y1).min().unwrap_or(0),
+                x2: regions.iter().map(|(r, _)| r.x2).max().unwrap_or(0),
+                y2: regions.iter().map(|(r, _)| r.y2).max().unwrap_or(0),
+            },
+            Bounds::Rect { x1, y1, x2, y2 } => Region {
+                x1: x1.min(x2),
+                y1: y1.min(y2),
+                x2: x1.max(x2),
+                y2: y1.max(y2),
+            },
+            Bounds::Relative { dx, dy } => {
+                let orig = match screen {
+                    Some(screen) if !screen.contains(orig) => screen.center(),
+                    _ => orig,
+                };
+                let mut region = Region {
+                    x1: orig.x - dx,
+                    y1: orig.y - dy,
+                    x2: orig.x + dx,
+                    y2: orig.y + dy,
+                };
+                if let Some(screen) = screen {
+                    region.x1 = region.x1.max(screen.left());
+                    region.x2 = region.x2.min(screen.right());
+                    region.y1 = region.y1.max(screen.top());
+                    region.y2 = region.y2.min(screen.bottom());
+                }
+                region
+            }
+        }
+    }
+}
+
+impl Default for Bounds {
+    fn default() -> Self {
+        Bounds::Relative { dx: 250, dy: 250 }
+    }
+}
+
+/// Bounds as written in config files, using the same coordinate order as the
+/// command line (e.g., `bounds = { absolute = [0, 0, 800, 600] }`).
+///
+/// Exactly one kind of bounds must be given, `weights` only goes with
+/// `regions`, and `inset` only goes with `active-window` or `window`.
+#[derive(Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+struct BoundsValue {
+    absolute: Option<[i32; 4]>,
+    relative: Option<[i32; 2]>,
+    center: Option<[u32; 2]>,
+    regions: Option<Vec<[i32; 4]>>,
+    weights: Option<Vec<u32>>,
+    #[serde(default)]
+    active_window: bool,
+    window: Option<String>,
+    inset: Option<u32>,
+}
+
+impl TryFrom<BoundsValue> for Bounds {
+    type Error = anyhow::Error;
+
+    fn try_from(value: BoundsValue) -> Result<Self> {
+        let kinds = [
+            value.absolute.is_some(),
+            value.relative.is_some(),
+            value.center.is_some(),
+            value.regions.is_some(),
+            value.active_window,
+            value.window.is_some(),
+        ];
+        ensure!(
+            kinds.iter().filter(|given| **given).count() == 1,
+            "bounds need exactly one of 'absolute', 'relative', 'center', 'regions', \
+             'active-window', or 'window'"
+        );
+
+        let bounds = if let Some([x1, y1, x2, y2]) = value.absolute {
+            Bounds::Rect { x1, y1, x2, y2 }
+        } else if let Some([dx, dy]) = value.relative {
+            Bounds::Relative { dx, dy }
+        } else if let Some([width, height]) = value.center {
+            Bounds::Center { width, height }
+        } else if let Some(regions) = value.regions {
+            ensure!(!regions.is_empty(), "bounds 'regions' can't be empty");
+            Bounds::from_rects(regions)
+        } else if let Some(title) = value.window {
+            Bounds::Window { title, inset: 0 }
+        } else {
+            Bounds::ActiveWindow { inset: 0 }
+        };
+
+        let bounds = match value.weights {
+            Some(weights) => bounds.with_weights(&weights)?,
+            None => bounds,
+        };
+        match value.inset {
+            Some(inset) => bounds.with_inset(inset),
+            None => Ok(bounds),
+        }
+    }
+}
+
+impl Bounds {
+    /// Returns a single rectangle, or equally weighted regions if there are
+    /// several.
+    fn from_rects(rects: Vec<[i32; 4]>) -> Self {
+        let mut bounds = rects.into_iter().map(Region::from).collect::<Vec<_>>();
+        if bounds.len() == 1 {
+            return Bounds::from(bounds.remove(0));
+        }
+
+        Bounds::Regions(bounds.into_iter().map(|region| (region, 1)).collect())
+    }
+}
+
+impl From<Region> for Bounds {
+    fn from(value: Region) -> Self {
+        Bounds::Rect {
+            x1: value.x1,
+            y1: value.y1,
+            x2: value.x2,
+            y2: value.y2,
         }
     }
 }
@@ -29,6 +539,19 @@ impl std::fmt::Display for Bounds {
                 write!(f, "absolute(p1=({x1}, {y1}), p2=({x2}, {y2}))")
             }
             Bounds::Relative { dx, dy } => write!(f, "relative(dx={dx}, dy={dy})"),
+            Bounds::Center { width, height } => write!(f, "center({width}x{height})"),
+            Bounds::ActiveWindow { inset } => write!(f, "active-window(inset={inset})"),
+            Bounds::Window { title, inset } => write!(f, "window('{title}', inset={inset})"),
+            Bounds::Regions(regions) => {
+                write!(f, "regions(")?;
+                for (i, (r, weight)) in regions.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "({}, {})-({}, {}) x{weight}", r.x1, r.y1, r.x2, r.y2)?;
+                }
+                write!(f, ")")
+            }
         }
     }
 }
@@ -36,17 +559,25 @@ impl std::fmt::Display for Bounds {
 impl From<&ArgMatches> for Bounds {
     fn from(value: &ArgMatches) -> Self {
         if value.contains_id("absolute-bounds") {
-            let coords = value
-                .get_many::<i32>("absolute-bounds")
+            // each use of the option is a separate region
+            let rects = value
+                .get_occurrences::<i32>("absolute-bounds")
                 .expect("values should be required by clap")
-                .copied()
-                .collect::<Vec<i32>>();
+                .map(|coords| {
+                    let coords = coords.copied().collect::<Vec<i32>>();
+                    [coords[0], coords[1], coords[2], coords[3]]
+                })
+                .collect();
 
-            return Bounds::Rect {
-                x1: coords[0],
-                y1: coords[1],
-                x2: coords[2],
-                y2: coords[3],
+            return Bounds::from_rects(rects);
+        }
+        if value.get_flag("active-window") {
+            return Bounds::ActiveWindow { inset: 0 };
+        }
+        if let Some(title) = value.get_one::<String>("window") {
+            return Bounds::Window {
+                title: title.clone(),
+                inset: 0,
             };
         }
         if value.contains_id("relative-bounds") {
@@ -62,6 +593,6 @@ impl From<&ArgMatches> for Bounds {
             };
         }
 
-        Bounds::Relative { dx: 250, dy: 250 }
+        Bounds::default()
     }
 }
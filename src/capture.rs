@@ -0,0 +1,67 @@
+use crate::bounds::Region;
+use crate::mouse::PointExt;
+
+use anyhow::{anyhow, bail, Result};
+use crossterm::event::{read, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use crossterm::terminal::{disable_raw_mode, enable_raw_mode};
+use mouse_rs::Mouse;
+
+/// Asks the user to point at two opposite corners, pressing a key at each,
+/// and returns the rectangle between them.
+pub fn corners() -> Result<Region> {
+    let mouse = Mouse::new();
+
+    println!("Capturing bounds (press q or Esc to cancel)");
+    let first = corner(&mouse, "first")?;
+    let second = corner(&mouse, "opposite")?;
+    if first == second {
+        bail!("both corners are at {first}, so there is nowhere to move");
+    }
+
+    Ok(Region::from([first.x, first.y, second.x, second.y]))
+}
+
+/// Waits for the user to press space or enter with the cursor on a corner,
+/// and returns where the cursor is.
+fn corner(mouse: &Mouse, which: &str) -> Result<PointExt> {
+    println!("  move the cursor to the {which} corner and press Space or Enter...");
+
+    // raw mode delivers key presses without waiting for a newline
+    enable_raw_mode()?;
+    let key = wait_for_key();
+    disable_raw_mode()?;
+
+    if !key? {
+        bail!("bounds capture was cancelled");
+    }
+    let p: PointExt = mouse
+        .get_position()
+        .map_err(|_| anyhow!("failed to get mouse position"))?
+        .into();
+    println!("  captured {p}");
+
+    Ok(p)
+}
+
+/// Returns `true` once a corner is confirmed, or `false` if the user asked to
+/// cancel instead.
+fn wait_for_key() -> Result<bool> {
+    loop {
+        let Event::Key(KeyEvent {
+            code,
+            modifiers,
+            kind: KeyEventKind::Press,
+            ..
+        }) = read()?
+        else {
+            continue;
+        };
+
+        match code {
+            KeyCode::Char(' ') | KeyCode::Enter => return Ok(true),
+            KeyCode::Char('q') | KeyCode::Esc => return Ok(false),
+            KeyCode::Char('c') if modifiers.contains(KeyModifiers::CONTROL) => return Ok(false),
+            _ => (),
+        }
+    }
+}
@@ -1,15 +1,290 @@
-use anyhow::{anyhow, ensure, Result};
+use crate::animation::Easing;
+use crate::backend::Backend;
+use crate::bounds::Axis;
+use crate::display::Units;
+use crate::pattern::Pattern;
+use crate::range::DurationRange;
+use crate::schedule::{Cron, Days, Hours};
+use crate::session::OnLock;
+use crate::target::Target;
+use crate::ui::StatusBar;
+
+use anyhow::{anyhow, bail, ensure, Result};
+use chrono::{NaiveTime, Weekday};
 use clap::builder::ValueParser;
 use clap::{arg, command, value_parser, Arg, ArgAction, Command};
 
+use std::ops::Bound::{self, Excluded, Included, Unbounded};
+use std::ops::RangeBounds;
+use std::path::PathBuf;
+use std::str::FromStr;
 use std::time::Duration;
 
 const INTERVAL_LONG_HELP: &str = "Specify how much time should elapse between \
-mouse movements. If not specified, defaults to 1 second.
+mouse movements. If not specified, defaults to 1 second (or the value from \
+the config file).
 
 A single number is parsed as SECONDS between movements. Numbers can \
 be specified as integers (e.g., 42) or floating point numbers (e.g., 0.42). \
-A single number argument must be a positive value.";
+A single number argument must be a positive value.
+
+Durations can also be written with units, combined in any order (e.g., 90s, \
+1m30s, 500ms, or 2h). Supported units are 'ms', 's', 'm', and 'h'.
+
+A range of durations (e.g., 5..30 or 30s..2m) picks a random interval within \
+it for each movement, so movements don't happen on a fixed period.";
+
+const CONFIG_LONG_HELP: &str = "Load options from a TOML file.
+
+Keys use the same names as the long options (e.g., 'pause-interval = 30'), \
+with 'animate' and 'auto-pause' in place of the negated flags. Bounds are \
+given as 'bounds = { relative = [DX, DY] }' or \
+'bounds = { absolute = [X1, Y1, X2, Y2] }', and input timing goes in an \
+'[input]' table. Options given on the command line override the file.
+
+If not specified, 'mouse-jiggler/config.toml' is loaded from the platform \
+config directory if it exists (e.g., '~/.config' on Linux, '%APPDATA%' on \
+Windows, or '~/Library/Application Support' on macOS). Use 'config path' to \
+see which file that is.";
+
+const PROFILE_LONG_HELP: &str = "Use a named profile from the config file.
+
+Profiles are defined in '[profile.NAME]' sections and override the values in \
+the '[default]' section. Without this option only '[default]' is used.";
+
+const JITTER_LONG_HELP: &str = "Randomly vary each interval by up to the \
+given percentage in either direction (e.g., 20 turns a 60s interval into \
+anything from 48s to 72s).
+
+Applies to both animated and non-animated movements, on top of any INTERVAL \
+range. Press 'j' while running to toggle it (20% if not specified).";
+
+const TARGET_LONG_HELP: &str = "Use an interval known to keep the given \
+application from reporting you as idle.
+
+If INTERVAL is also specified, it takes precedence over the preset. A warning \
+is printed if it is longer than the preset allows.";
+
+const LEARN_LONG_HELP: &str = "Sample your real cursor movements for a \
+while and print statistics about them (speed, pause lengths, jump sizes), \
+along with options that approximate your own behavior. The suggested \
+interval is the range the middle half of your pauses fell in, and the cursor \
+is read with '--backend'.
+
+The options are also saved to the config file (the default one unless \
+'--config' is given) as '[profile.learned]', replacing any previous one, \
+so they can be used with '--profile learned'.";
+
+const RECORD_LONG_HELP: &str = "Sample your real cursor movements and \
+write them to a file as a timestamped trace, for replaying later.
+
+Samples are taken at the fps from the config file (default: 60) unless \
+'--fps' is given.";
+
+const REPLAY_LONG_HELP: &str = "Play back a trace written by 'record' with \
+its original timing.
+
+With '--loop', the trace plays over and over, pausing whenever the mouse is \
+in use. '--offset' shifts every playback by a random amount so loops don't \
+repeat identically. Auto-pause and the pause interval come from the config \
+file.";
+
+const STAY_ON_SCREEN_LONG_HELP: &str = "Keep relative movements on the \
+display the mouse started on.
+
+Without this option, relative bounds near the edge of a display can spill \
+over onto an adjacent display. The display is looked up again whenever the \
+starting position changes (e.g., after auto-pausing).";
+
+const PATTERN_LONG_HELP: &str = "Trace a shape filling the bounds instead \
+of moving to random points, which is useful for demos, testing screen \
+recordings, and preventing burn-in.
+
+Patterns can be one of: circle, figure8, square, zigzag (sweeping back and \
+forth from top to bottom), random-walk (wandering a short way from the last \
+point each time), or hilbert (visiting every part of the bounds in turn \
+along a space-filling curve, for even coverage of OLED or plasma displays).";
+
+const GAUSSIAN_LONG_HELP: &str = "Pick points from a normal distribution \
+around the middle of the bounds, with a standard deviation of SIGMA pixels, \
+so the cursor tends to hover near one area instead of jumping across the \
+whole region.
+
+For relative bounds, the middle is the starting mouse position. Points that \
+would land outside the bounds are picked again.";
+
+const AVOID_RADIUS_LONG_HELP: &str = "Pick again when a point lands within \
+PX pixels of any of the last few points (see '--avoid-count'), so the cursor \
+visibly covers the bounds instead of bouncing between nearby spots.
+
+If no such point turns up after several tries (e.g., the bounds are small), \
+the last one picked is used anyway. Patterns are not affected.";
+
+const WAYPOINTS_LONG_HELP: &str = "Cycle through the absolute points in \
+FILE instead of picking points inside the bounds, which keeps the cursor \
+over specific safe parts of the screen.
+
+The file has one point per line written as X,Y (e.g., 640,360). Blank lines \
+and lines starting with '#' are ignored. Points are visited in order, or in \
+a new random order on every pass with '--shuffle-waypoints'.";
+
+const GRID_LONG_HELP: &str = "Snap each point to the nearest multiple of N \
+pixels on both axes, e.g., to keep the cursor lined up with the tiles of a \
+dashboard. Points stay inside the bounds where a grid line is close enough.";
+
+const MAX_DISTANCE_LONG_HELP: &str = "Never move the cursor further than \
+PX pixels in a single movement. Points further away are brought closer along \
+the same direction, which avoids jarring jumps across large bounds when not \
+animating.";
+
+const DRIFT_LONG_HELP: &str = "Move the cursor continuously at a slow, \
+constant speed (e.g., 5 pixels per second) in a wandering direction, \
+bouncing off the edges of the bounds, instead of moving once per interval.
+
+Intervals, patterns, and animation options have no effect in this mode, and \
+'--count' can't be used with it. Pausing, auto-pause, schedules, limits, and \
+watchdogs still apply, and drifting stops while any of them hold movements \
+back.";
+
+const WRAP_LONG_HELP: &str = "While drifting, continue from the opposite \
+edge of the bounds when reaching one, instead of bouncing off it. With \
+full-screen bounds this keeps the cursor sweeping across the whole screen, \
+e.g., to prevent burn-in on signage displays.
+
+This only has an effect with '--drift'.";
+
+const MICRO_LONG_HELP: &str = "Nudge the cursor 1px in a random direction \
+and immediately back each interval, instead of moving it to a new point. The \
+screen looks unchanged, but the OS still registers input.
+
+Bounds, patterns, and animation options have no effect in this mode.";
+
+const RETURN_LONG_HELP: &str = "Move back to where the cursor started \
+after each movement, so it is left where you put it between intervals.
+
+Each interval is split evenly between the way out and the way back. Dwelling \
+happens after returning, and the starting point follows the cursor if you \
+move it yourself.";
+
+const CLAMP_BACK_LONG_HELP: &str = "If the cursor is outside the bounds \
+when a movement starts (e.g., after you bumped the mouse), move it to the \
+nearest point inside them instead of a random point, which could be across \
+the screen. Movements continue as usual from there.";
+
+const ABSOLUTE_BOUNDS_LONG_HELP: &str = "Restrict movements inside a \
+rectangle with absolute coordinates.
+
+Give this option more than once to move between several separate regions \
+(e.g., safe areas on two monitors). A region is picked at random for each \
+movement, weighted by '--weights' if given.";
+
+const ACTIVE_WINDOW_LONG_HELP: &str = "Restrict movements inside the \
+focused window. The window is looked up again before each movement, so this \
+follows focus as it changes.
+
+This needs 'xdotool' on Linux (X11 only), and accessibility permissions for \
+the terminal on macOS.";
+
+const WINDOW_LONG_HELP: &str = "Restrict movements inside the first visible \
+window with TITLE in its title or class (ignoring case), e.g., 'Notepad'. \
+The window is looked up again before each movement, and movements pause \
+while no window matches.
+
+This needs 'xdotool' on Linux (X11 only), and accessibility permissions for \
+the terminal on macOS.";
+
+const INSET_LONG_HELP: &str = "Stay PX pixels away from the edges of the \
+window given with '--active-window' or '--window', e.g., to keep clear of \
+title bars and scroll bars.";
+
+const CAPTURE_BOUNDS_LONG_HELP: &str = "Capture absolute bounds for this \
+session by pointing at them: move the cursor to one corner and press Space \
+or Enter, then do the same at the opposite corner.
+
+Use '--print-captured' to also print the options that give the same bounds, \
+to reuse them later.";
+
+const WEIGHTS_LONG_HELP: &str = "Relative chance of picking each region \
+given with '--absolute-bounds', in the same order (e.g., '--weights 3,1' \
+picks the first region three times as often as the second).";
+
+const CENTER_LONG_HELP: &str = "Restrict movements inside a rectangle of \
+the given size (e.g., 800x600) centered on the primary display, or on the \
+monitor given with '--monitor'. This is the same as '--absolute-bounds' with \
+the coordinates worked out for you.";
+
+const MONITOR_LONG_HELP: &str = "Keep movements on one monitor, selected \
+by its index (starting from 1) or name as listed by the 'displays' \
+subcommand.
+
+Absolute bounds are measured from the monitor's top left corner, so \
+'--absolute-bounds 0,0,800,600' is the top left of that monitor wherever it \
+sits in the desktop (including at negative coordinates). Relative bounds are \
+kept on the monitor, and centered on it if the cursor starts elsewhere.";
+
+const MARGIN_LONG_HELP: &str = "Never move within PX pixels of the outer \
+edges of the desktop, avoiding hot corners, auto-hiding taskbars, and edges \
+that scroll or switch workspaces.
+
+Bounds are shrunk to fit. Edges shared between displays don't count.";
+
+const EXCLUDE_LONG_HELP: &str = "Never move to a point inside this \
+rectangle, e.g., to stay clear of a button inside the bounds. Give this \
+option more than once to exclude several rectangles.
+
+Coordinates are absolute, like '--absolute-bounds', and measured from the \
+monitor's top left corner with '--monitor'. It is an error for the excluded \
+rectangles to cover all of the bounds.";
+
+const LOCK_AXIS_LONG_HELP: &str = "Keep the cursor's X or Y coordinate \
+fixed, so it only moves vertically or horizontally (e.g., '--lock-axis y' \
+stays on one row). The coordinate is taken from wherever the cursor is, or \
+the nearest row or column inside the bounds.
+
+Paths are kept straight, so wobble, curves, and splines have no effect.";
+
+const UNITS_LONG_HELP: &str = "Units for absolute coordinates given with \
+'--absolute-bounds' and '--exclude' (or in the config file).
+
+Physical pixels match what you measure in a screenshot. Logical pixels are \
+divided by the display's scale factor (e.g., 1.5 at 150%), which is what \
+macOS calls points. Coordinates are scaled from the top left corner of the \
+display they are on.";
+
+const INHIBIT_SLEEP_LONG_HELP: &str = "Also ask the OS to keep the system and \
+display awake while running, for power policies that sleep regardless of \
+mouse movement. The request is released on exit.
+
+This uses SetThreadExecutionState on Windows, 'caffeinate' on macOS, and \
+'systemd-inhibit' (a logind idle and sleep inhibitor) on Linux.";
+
+const DOCTOR_LONG_HELP: &str = "Check whether the cursor can be moved here, \
+and why not.
+
+This prints the platform and display server, checks permissions needed to \
+move the cursor (Accessibility on macOS, /dev/uinput on Linux), and tries each \
+backend available on this platform by nudging the cursor 1px and back. Each \
+check is reported as pass, FAIL, or skip, with a hint on fixing failures.";
+
+const BACKEND_LONG_HELP: &str = "How cursor movements reach the system. If \
+not specified, defaults to 'mouse-rs', which sets the cursor position directly \
+on every platform.
+
+On Windows, 'send-input' moves the cursor with relative motion events \
+instead, like a real mouse would. Use this if games, remote desktop clients, \
+or idle detectors ignore the default backend. Movements may be scaled by \
+'Enhance pointer precision', but are corrected on the next frame.
+
+On Linux, 'uinput' creates a virtual pointer device, which works on Wayland \
+compositors and consoles where the default backend can't move the cursor. \
+This needs write access to /dev/uinput. Since the cursor position can't be \
+read back, auto-pausing won't notice you moving the mouse. With X11, 'xtest' \
+fakes real pointer motion instead of warping the cursor, for idle monitors \
+that only reset on input events.
+
+On macOS, 'cgevent' posts mouse moved events like a real mouse. Every backend \
+on macOS needs Accessibility permission for the app running mouser, which is \
+checked at startup.";
 
 const FPS_LONG_HELP: &str = "Number of animation frames per second. If not \
 specified, defaults to 60.
@@ -20,6 +295,80 @@ an interval of 0.5s at 60fps would generate 30 animation frames per interval. \
 In general, lower interval times require a higher fps value to make animations \
 appear smooth.";
 
+const MOVE_DURATION_LONG_HELP: &str = "Animate each movement over this long, \
+then rest at the target for the remainder of the interval. By default, \
+animations take the entire interval.
+
+Durations longer than the interval are shortened to the interval. Uses the \
+same format as INTERVAL (e.g., '2s' or '500ms').";
+
+const SPEED_LONG_HELP: &str = "Move the cursor at a constant speed, in \
+pixels per second. Animations take as long as the distance to each target \
+needs, and the cursor rests for the remainder of the interval.
+
+Targets far enough away may take longer than the interval to reach.";
+
+const FITTS_LONG_HELP: &str = "Take as long as a person would to reach \
+each point, following Fitts' law. Short hops are quick and long traversals \
+take proportionally longer, varying by up to 15% either way.
+
+DIFFICULTY is the number of seconds taken per bit of the movement's index of \
+difficulty, log2(distance / 20px + 1). Values around 0.1 to 0.2 are typical \
+of a mouse. The cursor rests for the remainder of the interval.";
+
+const EASING_LONG_HELP: &str = "Timing curve used to animate each movement \
+(default: ease-in-out).
+
+Curves can be one of: linear, ease-in-out, or the CSS keywords ease, \
+ease-in, and ease-out. Custom curves are written the same as CSS \
+(e.g., 'cubic-bezier(0.25,0.1,0.25,1.0)'), where both x values must be \
+between 0 and 1.
+
+'spring' animates with a damped spring that overshoots the target slightly \
+before settling. Its stiffness and damping can be given as \
+'spring(STIFFNESS,DAMPING)' (default: spring(100,10)). Lower damping \
+overshoots more, and damping of at least twice the square root of the \
+stiffness never overshoots.";
+
+const WOBBLE_LONG_HELP: &str = "Sway to either side of the straight path \
+between points by up to PIXELS, following smooth noise so the cursor \
+doesn't travel in a perfectly straight line. Movements still start and end \
+exactly on their points.
+
+Use 0 to disable (default: 0).";
+
+const CURVE_LONG_HELP: &str = "Move along arcs instead of straight lines. \
+Each movement bends to a random side by up to AMOUNT times the distance \
+between its points (e.g., 0.3 for up to 30%).
+
+Use 0 to disable (default: 0). With '--speed', the speed applies to the \
+straight-line distance, so arcs are travelled slightly faster.";
+
+const SPLINE_LONG_HELP: &str = "Move along a smooth spline through 2 to 4 \
+random waypoints on the way to each point, instead of a straight line.
+
+Waypoints are picked inside the rectangle between the start and end of each \
+movement, so the path stays close to the bounds. With '--speed', the speed \
+applies to the straight-line distance, so splines are travelled faster.";
+
+const CHAIN_LONG_HELP: &str = "Blend consecutive movements so the cursor \
+keeps its velocity when it reaches a point, instead of stopping and \
+accelerating again.
+
+Each movement follows a Hermite curve that leaves with the velocity the \
+previous movement arrived with. The curve stays within the rectangle between \
+the start and end of each movement, so it never overshoots the bounds. \
+Movements interrupted by a key press or auto-pause, or followed by a rest or \
+'--dwell', start again from rest.";
+
+const MAX_ACCEL_LONG_HELP: &str = "Limit cursor acceleration to the given \
+number of pixels per second squared.
+
+Movements accelerate at this rate up to a constant speed, then decelerate at \
+the same rate to stop at the target (a trapezoidal velocity profile). If the \
+target is too far to reach within the interval at this acceleration, the \
+movement takes longer than the interval.";
+
 const NO_ANIMATE_LONG_HELP: &str = "Do not animate mouse movements. Instead, \
 'place' the mouse at each point.
 
@@ -35,30 +384,334 @@ calculated region (for example, if your physical mouse might be bumped or moved)
 WARNING: If '-a' is NOT specified, you won't be able to move your mouse until \
 this application quits.";
 
+const RESUME_LONG_HELP: &str = "Resume a movement interrupted by auto-pause \
+once the pause ends.
+
+The cursor animates toward the original target from wherever it was left, \
+instead of the target being discarded and a new point sampled.";
+
+const DEBOUNCE_LONG_HELP: &str = "Milliseconds that input must be quiet \
+before a key command is considered finished (default: 50).
+
+Slow or remote terminals (e.g., over SSH) may deliver a single key press in \
+pieces. Increase this value if one press toggles a command more than once.";
+
+const HOOKS_LONG_HELP: &str = "Run a shell command when the application \
+starts, pauses, resumes, or exits. Commands run in the background, except \
+on exit where the application waits for the command to finish.
+
+Each command gets details about the event in its environment:
+
+  MOUSER_EVENT     start, pause, resume, or exit
+  MOUSER_REASON    why it happened (e.g., key, auto, until, deadline, quit)
+  MOUSER_ELAPSED   whole seconds since startup
+
+Pauses include auto-pausing when the mouse is in use (reason 'auto'), and \
+the session being locked with '--on-lock pause' (reason 'locked').";
+
+const SIMULATE_USER_LONG_HELP: &str = "Randomly push the cursor away while \
+it is being moved, as if a user had grabbed the mouse.
+
+This triggers auto-pausing (and '--resume', if given) about every 20 seconds \
+of movement, so those options can be tuned or demonstrated without touching \
+the mouse.";
+
+const SEED_LONG_HELP: &str = "Seed the random number generator so the same \
+points, intervals, and dwell times are chosen on every run.
+
+Movements interrupted by the user, or any other timing-dependent behavior, \
+can still make runs differ.";
+
+const ONCE_LONG_HELP: &str = "Make a single movement and exit, without \
+starting the TUI. The movement is animated over INTERVAL unless \
+'--no-animate' is given.
+
+This is useful for running from an external scheduler like cron or Task \
+Scheduler. Options that only matter while running (e.g., '--duration' or \
+'--watchdog') are ignored.";
+
+const START_AFTER_LONG_HELP: &str = "Wait this long before the first \
+movement, showing a countdown. Relative bounds are centered on wherever the \
+mouse is once the countdown ends.
+
+Uses the same format as INTERVAL (e.g., '30s').";
+
+const DAILY_LIMIT_LONG_HELP: &str = "Stop moving the mouse once the \
+application has been active for this long in a single calendar day.
+
+Active time is kept in the state file, so it adds up across restarts. Time \
+spent paused or waiting doesn't count. Once reached, the application idles \
+until the next day.";
+
+const DURATION_LONG_HELP: &str = "Exit after running for this long, \
+including any time spent paused or waiting. The time remaining is shown in \
+the status line.
+
+Uses the same format as INTERVAL (e.g., '90m' or '2h').";
+
+const UNTIL_LONG_HELP: &str = "Exit when the local clock reaches TIME, \
+written as HH:MM in 24-hour time (e.g., 17:30). If TIME has already passed \
+today, the application runs until that time tomorrow.
+
+With '--until-pause', movements are paused at TIME instead, and can be \
+unpaused as usual.";
+
+const ACTIVE_HOURS_LONG_HELP: &str = "Only move the mouse between two local \
+times each day, written as HH:MM-HH:MM in 24-hour time (e.g., 09:00-17:00). \
+The end time is exclusive, and ranges may wrap past midnight (e.g., \
+22:00-06:00).
+
+Outside of these hours the application idles and shows when it will resume.";
+
+const ACTIVE_DAYS_LONG_HELP: &str = "Only move the mouse on certain days of \
+the week, written as a comma separated list of days or ranges of days (e.g., \
+mon-fri or mon,wed,fri).
+
+Combined with '--active-hours', the hours apply to each of these days.";
+
+const CRON_LONG_HELP: &str = "Only move the mouse during minutes matching a \
+cron expression, written as five fields: MIN HOUR DAY MONTH WEEKDAY.
+
+Each field is '*' or a comma separated list of values and ranges, with an \
+optional step (e.g., '*/5' or '9-17/2'). Weekdays are 0-7, where both 0 and \
+7 are Sunday. For example, '*/5 9-17 * * 1-5' moves every fifth minute \
+between 9:00 and 17:59 on weekdays.
+
+This can be combined with '--active-hours' and '--active-days', in which \
+case all of them must match.";
+
+const WATCHDOG_LONG_HELP: &str = "Periodically run a shell command and only \
+move the mouse while it exits successfully.
+
+This can tie movements to any external condition, like a VPN being \
+connected or a URL being reachable (e.g., 'curl -sf https://example.com'). \
+The command is checked before the first movement and then every \
+'--watchdog-interval', and a command still running after 10 seconds counts \
+as failed.";
+
+const ON_LOCK_LONG_HELP: &str = "What to do while the session is locked: \
+'continue' moving (the default), 'pause' until it is unlocked, or 'quit'.
+
+The lock state is checked every couple of seconds, from the session flags on \
+Windows, the window server session on macOS, and logind's LockedHint on Linux \
+(which most screen lockers set). Pausing fires the pause and resume hooks \
+with a reason of 'locked' and 'unlocked'.";
+
+const STATUSBAR_LONG_HELP: &str = "Print status updates for a desktop status \
+bar instead of drawing the terminal interface.
+
+A line (or JSON object for waybar) is printed to stdout each time the status \
+changes. Key commands are disabled in this mode, so the application is \
+stopped by terminating it.";
+
+const IDLE_THRESHOLD_LONG_HELP: &str = "Only move the mouse when the system \
+has been idle for nearly this long (e.g., your screensaver or away-status \
+timeout).
+
+Movements happen once idle time reaches the threshold minus '--idle-margin', \
+so the idle timer never expires but no more synthetic input is produced than \
+necessary. Any real input resets the idle time and postpones the next \
+movement.";
+
+const DWELL_LONG_HELP: &str = "Rest at each target for a random time \
+before the next movement begins.
+
+Accepts a single duration or a range of durations (e.g., 2..10) in the same \
+format as INTERVAL. The rest time is separate from the movement interval.";
+
+const DWELL_JITTER_LONG_HELP: &str = "Drift around each target by up to \
+PX pixels while dwelling (see '--dwell'), like a hand resting on the mouse, \
+then settle back on the target.
+
+Values of 1 or 2 look the most natural.";
+
+/// Options that can't be used together, by argument id.
+///
+/// Clap applies these to the command line, and `Config::validate` applies
+/// them again once config files are merged in.
+pub const CONFLICTS: &[(&str, &str)] = &[
+    ("absolute-bounds", "relative-bounds"),
+    ("active-window", "absolute-bounds"),
+    ("active-window", "relative-bounds"),
+    ("active-window", "stay-on-screen"),
+    ("window", "absolute-bounds"),
+    ("window", "relative-bounds"),
+    ("window", "active-window"),
+    ("window", "stay-on-screen"),
+    ("capture-bounds", "absolute-bounds"),
+    ("capture-bounds", "relative-bounds"),
+    ("capture-bounds", "active-window"),
+    ("capture-bounds", "window"),
+    ("capture-bounds", "center"),
+    ("capture-bounds", "statusbar"),
+    ("capture-bounds", "once"),
+    ("center", "absolute-bounds"),
+    ("center", "relative-bounds"),
+    ("center", "active-window"),
+    ("center", "window"),
+    ("stay-on-screen", "absolute-bounds"),
+    ("stay-on-screen", "center"),
+    ("lock-axis", "micro"),
+    ("lock-axis", "drift"),
+    ("lock-axis", "dwell-jitter"),
+    ("gaussian", "pattern"),
+    ("avoid-radius", "pattern"),
+    ("waypoints", "pattern"),
+    ("waypoints", "gaussian"),
+    ("drift", "pattern"),
+    ("drift", "gaussian"),
+    ("drift", "waypoints"),
+    ("drift", "micro"),
+    ("drift", "return"),
+    ("drift", "count"),
+    ("micro", "pattern"),
+    ("micro", "gaussian"),
+    ("micro", "max-distance"),
+    ("return", "micro"),
+    ("clamp-back", "micro"),
+    ("clamp-back", "drift"),
+    ("pause-interval", "no-autopause"),
+    ("fps", "no-animate"),
+    ("move-duration", "no-animate"),
+    ("speed", "no-animate"),
+    ("speed", "move-duration"),
+    ("fitts", "no-animate"),
+    ("fitts", "speed"),
+    ("fitts", "move-duration"),
+    ("easing", "no-animate"),
+    ("easing", "speed"),
+    ("easing", "chain"),
+    ("easing", "max-accel"),
+    ("wobble", "no-animate"),
+    ("curve", "no-animate"),
+    ("curve", "chain"),
+    ("spline", "no-animate"),
+    ("spline", "chain"),
+    ("spline", "curve"),
+    ("chain", "no-animate"),
+    ("max-accel", "no-animate"),
+    ("max-accel", "chain"),
+    ("resume", "no-autopause"),
+    ("paused", "statusbar"),
+    ("once", "paused"),
+    ("once", "statusbar"),
+    ("count", "once"),
+];
+
 pub fn build() -> Command {
-    command!()
+    let cmd = command!()
         .disable_help_flag(true)
         .disable_version_flag(true)
         .after_help("Use '--help' for detailed information")
         .after_long_help("Use '-h' for brief information")
+        .args_conflicts_with_subcommands(true)
+        .subcommand(
+            Command::new("config")
+                .about("Inspect the config file")
+                .subcommand_required(true)
+                .subcommand(
+                    Command::new("path").about("Print the config file loaded when --config isn't given"),
+                )
+                .subcommand(
+                    Command::new("init")
+                        .about("Write a commented example config file")
+                        .arg(
+                            arg!([PATH] "Where to write the file (default: the platform config directory)")
+                                .value_parser(value_parser!(PathBuf)),
+                        )
+                        .arg(arg!(-f --force "Overwrite the file if it already exists")),
+                ),
+        )
+        .subcommand(
+            Command::new("displays")
+                .about("List connected displays, for writing bounds or choosing a monitor"),
+        )
+        .subcommand(
+            Command::new("doctor")
+                .about("Check whether the cursor can be moved here, and why not")
+                .long_about(DOCTOR_LONG_HELP),
+        )
+        .subcommand(
+            Command::new("learn")
+                .about("Sample real cursor movements and suggest matching options")
+                .long_about(LEARN_LONG_HELP)
+                .arg(
+                    arg!([DURATION] "How long to sample for (default: 60 seconds)")
+                        .default_value("60")
+                        .hide_default_value(true)
+                        .value_parser(ValueParser::new(parse_interval)),
+                ),
+        )
+        .subcommand(
+            Command::new("record")
+                .about("Record real cursor movements to a trace file")
+                .long_about(RECORD_LONG_HELP)
+                .arg(
+                    arg!(-o --output <FILE> "Where to write the trace (e.g., moves.json)")
+                        .required(true)
+                        .value_parser(value_parser!(PathBuf)),
+                )
+                .arg(
+                    arg!([DURATION] "How long to record for (default: 60 seconds)")
+                        .default_value("60")
+                        .hide_default_value(true)
+                        .value_parser(ValueParser::new(parse_interval)),
+                )
+                .arg(
+                    arg!(-f --fps <FPS> "Number of samples per second")
+                        .value_parser(ValueParser::new(parse_fps)),
+                ),
+        )
+        .subcommand(
+            Command::new("replay")
+                .about("Play back a recorded trace file")
+                .long_about(REPLAY_LONG_HELP)
+                .arg(
+                    arg!(<FILE> "Trace file written by 'record'")
+                        .value_parser(value_parser!(PathBuf)),
+                )
+                .arg(arg!(-l --"loop" "Play the trace over and over"))
+                .arg(
+                    arg!(--offset <PX> "Shift each playback randomly by up to PX pixels")
+                        .value_parser(value_parser!(u32)),
+                ),
+        )
         .arg(
-            arg!([INTERVAL] "Duration between movements (see '--help' for formatting)")
+            arg!([INTERVAL] "Duration or range of durations between movements (see '--help' for formatting)")
                 .long_help(INTERVAL_LONG_HELP)
-                .default_value("1")
-                .hide_default_value(true)
-                .value_parser(ValueParser::new(parse_interval)),
+                .value_parser(ValueParser::new(parse_duration_range)),
+        )
+        .arg(
+            arg!(-j --jitter <PERCENT> "Randomly vary each interval by up to PERCENT")
+                .long_help(JITTER_LONG_HELP)
+                .value_parser(ValueParser::new(parse_percent)),
+        )
+        .arg(
+            arg!(-c --config <PATH> "Load options from a TOML config file")
+                .long_help(CONFIG_LONG_HELP)
+                .value_parser(value_parser!(PathBuf)),
+        )
+        .arg(
+            arg!(--profile <NAME> "Use a named profile from the config file")
+                .long_help(PROFILE_LONG_HELP),
+        )
+        .arg(
+            arg!(-t --target <APP> "Use an interval preset for an application")
+                .long_help(TARGET_LONG_HELP)
+                .value_parser(value_parser!(Target)),
         )
         .next_help_heading("Mouse Options")
         .arg(Arg::new("absolute-bounds")
             .short('b')
             .long("absolute-bounds")
-            .help("Restrict movements inside a rectangle with absolute coordinates")
+            .help("Restrict movements inside a rectangle with absolute coordinates (repeatable)")
+            .long_help(ABSOLUTE_BOUNDS_LONG_HELP)
+            .action(ArgAction::Append)
             .num_args(4)
             .value_names(["X1", "Y1", "X2", "Y2"])
             .value_delimiter(',')
             .value_parser(value_parser!(i32))
-            .allow_negative_numbers(true)
-            .conflicts_with("relative-bounds"))
+            .allow_negative_numbers(true))
         .arg(Arg::new("relative-bounds")
             .short('r')
             .long("relative-bounds")
@@ -67,18 +720,189 @@ pub fn build() -> Command {
             .value_delimiter(',')
             .value_parser(value_parser!(i32))
             .value_names(["DX", "DY"]))
+        .arg(
+            arg!(--"active-window" "Restrict movements inside the focused window")
+                .long_help(ACTIVE_WINDOW_LONG_HELP),
+        )
+        .arg(
+            arg!(--window <TITLE> "Restrict movements inside a window, by title or class")
+                .long_help(WINDOW_LONG_HELP),
+        )
+        .arg(
+            arg!(--inset <PX> "Stay PX pixels inside the edges of window bounds")
+                .long_help(INSET_LONG_HELP)
+                .value_parser(value_parser!(u32)),
+        )
+        .arg(
+            arg!(--"capture-bounds" "Capture absolute bounds by pointing at two corners")
+                .long_help(CAPTURE_BOUNDS_LONG_HELP),
+        )
+        .arg(
+            arg!(--"print-captured" "Print options giving the same bounds as --capture-bounds")
+                .requires("capture-bounds"),
+        )
+        .arg(
+            arg!(--weights <WEIGHTS> "Relative chance of picking each absolute bounds region")
+                .long_help(WEIGHTS_LONG_HELP)
+                .num_args(1..)
+                .value_delimiter(',')
+                .value_parser(value_parser!(u32).range(1..))
+                .requires("absolute-bounds"),
+        )
+        .arg(
+            arg!(--center <SIZE> "Restrict movements to a WxH rectangle on the primary display")
+                .long_help(CENTER_LONG_HELP)
+                .value_parser(ValueParser::new(parse_size)),
+        )
+        .arg(
+            arg!(--"stay-on-screen" "Keep relative movements on the starting display")
+                .long_help(STAY_ON_SCREEN_LONG_HELP),
+        )
+        .arg(
+            arg!(--monitor <MONITOR> "Keep movements on a monitor, by index or name")
+                .long_help(MONITOR_LONG_HELP),
+        )
+        .arg(
+            arg!(--margin <PX> "Keep PX pixels away from the edges of the desktop")
+                .long_help(MARGIN_LONG_HELP)
+                .value_parser(value_parser!(u32)),
+        )
+        .arg(Arg::new("exclude")
+            .long("exclude")
+            .help("Never move inside a rectangle with absolute coordinates (repeatable)")
+            .long_help(EXCLUDE_LONG_HELP)
+            .action(ArgAction::Append)
+            .num_args(4)
+            .value_names(["X1", "Y1", "X2", "Y2"])
+            .value_delimiter(',')
+            .value_parser(value_parser!(i32)))
+        .arg(
+            arg!(--"lock-axis" <AXIS> "Keep the cursor's X or Y fixed")
+                .long_help(LOCK_AXIS_LONG_HELP)
+                .value_parser(value_parser!(Axis)),
+        )
+        .arg(
+            arg!(--units <UNITS> "Units for absolute coordinates")
+                .long_help(UNITS_LONG_HELP)
+                .value_parser(value_parser!(Units)),
+        )
+        .arg(
+            arg!(--pattern <PATTERN> "Trace a shape instead of moving randomly (see '--help')")
+                .long_help(PATTERN_LONG_HELP)
+                .value_parser(ValueParser::new(Pattern::from_str)),
+        )
+        .arg(
+            arg!(--gaussian <SIGMA> "Favor points near the middle of the bounds (e.g., 80)")
+                .long_help(GAUSSIAN_LONG_HELP)
+                .value_parser(ValueParser::new(parse_f64_in("sigma", 1.0..))),
+        )
+        .arg(
+            arg!(--"avoid-radius" <PX> "Avoid points near recent ones")
+                .long_help(AVOID_RADIUS_LONG_HELP)
+                .value_parser(value_parser!(u32).range(1..)),
+        )
+        .arg(
+            arg!(--"avoid-count" <K> "Number of recent points to avoid (default: 5)")
+                .value_parser(value_parser!(usize).range(1..)),
+        )
+        .arg(
+            arg!(--waypoints <FILE> "Cycle through points listed in a file")
+                .long_help(WAYPOINTS_LONG_HELP)
+                .value_parser(value_parser!(PathBuf)),
+        )
+        .arg(
+            arg!(--"shuffle-waypoints" "Visit waypoints in a random order"),
+        )
+        .arg(
+            arg!(--grid <N> "Snap points to an N-pixel grid")
+                .long_help(GRID_LONG_HELP)
+                .value_parser(value_parser!(u32).range(1..)),
+        )
+        .arg(
+            arg!(--"max-distance" <PX> "Never move further than PX pixels at once")
+                .long_help(MAX_DISTANCE_LONG_HELP)
+                .value_parser(value_parser!(u32).range(1..)),
+        )
+        .arg(
+            arg!(--drift <PX_PER_SEC> "Drift continuously at a slow speed instead of hopping")
+                .long_help(DRIFT_LONG_HELP)
+                .value_parser(ValueParser::new(parse_f64_in("drift speed", POSITIVE))),
+        )
+        .arg(
+            arg!(--wrap "Drift off one edge of the bounds and back in at the opposite edge")
+                .long_help(WRAP_LONG_HELP),
+        )
+        .arg(
+            arg!(--micro "Nudge the cursor 1px and back instead of moving it")
+                .long_help(MICRO_LONG_HELP),
+        )
+        .arg(
+            arg!(--"return" "Move back to the starting point after each movement")
+                .long_help(RETURN_LONG_HELP),
+        )
+        .arg(
+            arg!(--"clamp-back" "Bring the cursor back in at the nearest point of the bounds")
+                .long_help(CLAMP_BACK_LONG_HELP),
+        )
+        .arg(
+            arg!(--dwell <DURATION> "Rest at each target for a random duration (e.g., 2..10)")
+                .long_help(DWELL_LONG_HELP)
+                .value_parser(ValueParser::new(parse_duration_range)),
+        )
+        .arg(
+            arg!(--"dwell-jitter" <PX> "Drift by up to PX pixels while dwelling")
+                .long_help(DWELL_JITTER_LONG_HELP)
+                .value_parser(ValueParser::new(parse_f64_in("dwell jitter", POSITIVE))),
+        )
         .arg(arg!(-p --"pause-interval" <DURATION> "Set the pause interval for movements when in use")
-            .conflicts_with("no-autopause")
-            .default_value("60")
-            .hide_default_value(true)
             .value_parser(ValueParser::new(parse_interval)))
         .arg(
             arg!(-f --fps <FPS> "Number of animation frames per second (default: 60)")
                 .long_help(FPS_LONG_HELP)
-                .default_value("60")
-                .hide_default_value(true)
-                .value_parser(ValueParser::new(parse_fps))
-                .conflicts_with("no-animate"),
+                .value_parser(ValueParser::new(parse_fps)),
+        )
+        .arg(
+            arg!(--"move-duration" <DURATION> "Animate over this long, then rest for the rest of the interval")
+                .long_help(MOVE_DURATION_LONG_HELP)
+                .value_parser(ValueParser::new(parse_interval)),
+        )
+        .arg(
+            arg!(--speed <PX_PER_SEC> "Move at a constant speed instead of over the interval")
+                .long_help(SPEED_LONG_HELP)
+                .value_parser(ValueParser::new(parse_f64_in("speed", POSITIVE))),
+        )
+        .arg(
+            arg!(--fitts <DIFFICULTY> "Scale animation time with distance (Fitts' law)")
+                .long_help(FITTS_LONG_HELP)
+                .value_parser(ValueParser::new(parse_f64_in("difficulty", POSITIVE))),
+        )
+        .arg(
+            arg!(--easing <CURVE> "Timing curve for animations (see '--help' for curves)")
+                .long_help(EASING_LONG_HELP)
+                .value_parser(ValueParser::new(Easing::from_str)),
+        )
+        .arg(
+            arg!(--wobble <PIXELS> "Sway to either side of the path by up to PIXELS (default: 0)")
+                .long_help(WOBBLE_LONG_HELP)
+                .value_parser(ValueParser::new(parse_f64_in("wobble", 0.0..))),
+        )
+        .arg(
+            arg!(--curve <AMOUNT> "Arc paths by up to AMOUNT of their length (e.g., 0.3)")
+                .long_help(CURVE_LONG_HELP)
+                .value_parser(ValueParser::new(parse_f64_in("curve", 0.0..=1.0))),
+        )
+        .arg(
+            arg!(--spline "Pass through random waypoints on the way to each point")
+                .long_help(SPLINE_LONG_HELP),
+        )
+        .arg(
+            arg!(--chain "Keep velocity continuous between movements")
+                .long_help(CHAIN_LONG_HELP),
+        )
+        .arg(
+            arg!(--"max-accel" <ACCEL> "Limit acceleration of animations (px/s²)")
+                .long_help(MAX_ACCEL_LONG_HELP)
+                .value_parser(ValueParser::new(parse_f64_in("acceleration", POSITIVE))),
         )
         .arg(
             arg!(-a --"no-animate" "Do not animate mouse movements")
@@ -88,9 +912,144 @@ pub fn build() -> Command {
             arg!(-P --"no-autopause" "Do not pause mouse movements if the mouse is in use")
                 .long_help(NO_AUTO_PAUSE_LONG_HELP),
         )
+        .arg(
+            arg!(--resume "Resume interrupted movements after auto-pausing")
+                .long_help(RESUME_LONG_HELP),
+        )
+        .arg(
+            arg!(--paused "Start paused, waiting for 'p' before moving"),
+        )
+        .arg(
+            arg!(--once "Make a single movement and exit")
+                .long_help(ONCE_LONG_HELP),
+        )
+        .arg(
+            arg!(--"start-after" <DURATION> "Wait this long before the first movement")
+                .long_help(START_AFTER_LONG_HELP)
+                .value_parser(ValueParser::new(parse_interval)),
+        )
+        .arg(
+            arg!(--"daily-limit" <DURATION> "Stop moving after being active this long in a day")
+                .long_help(DAILY_LIMIT_LONG_HELP)
+                .value_parser(ValueParser::new(parse_interval)),
+        )
+        .arg(
+            arg!(-d --duration <DURATION> "Exit after running for this long")
+                .long_help(DURATION_LONG_HELP)
+                .value_parser(ValueParser::new(parse_interval)),
+        )
+        .arg(
+            arg!(-n --count <N> "Exit after N movements, printing a summary")
+                .value_parser(value_parser!(u64).range(1..)),
+        )
+        .arg(
+            arg!(--until <TIME> "Exit at a local time of day (HH:MM)")
+                .long_help(UNTIL_LONG_HELP)
+                .value_parser(ValueParser::new(parse_time)),
+        )
+        .arg(
+            arg!(--"until-pause" "Pause instead of exiting at the '--until' time"),
+        )
+        .arg(
+            arg!(--"active-hours" <HOURS> "Only move between these local times (e.g., 09:00-17:00)")
+                .long_help(ACTIVE_HOURS_LONG_HELP)
+                .value_parser(ValueParser::new(parse_hours)),
+        )
+        .arg(
+            arg!(--"active-days" <DAYS> "Only move on these days of the week (e.g., mon-fri)")
+                .long_help(ACTIVE_DAYS_LONG_HELP)
+                .value_parser(ValueParser::new(parse_days)),
+        )
+        .arg(
+            arg!(--cron <EXPR> "Only move in minutes matching a cron expression")
+                .long_help(CRON_LONG_HELP)
+                .value_parser(ValueParser::new(Cron::from_str)),
+        )
+        .arg(
+            arg!(--watchdog <COMMAND> "Only move while a shell command exits successfully")
+                .long_help(WATCHDOG_LONG_HELP),
+        )
+        .arg(
+            arg!(--"watchdog-interval" <DURATION> "Time between watchdog checks (default: 30 seconds)")
+                .value_parser(ValueParser::new(parse_interval)),
+        )
+        .arg(
+            arg!(--"on-lock" <ACTION> "What to do while the session is locked (default: continue)")
+                .long_help(ON_LOCK_LONG_HELP)
+                .value_parser(value_parser!(OnLock)),
+        )
+        .arg(
+            arg!(--statusbar <FORMAT> "Print status for a desktop status bar instead of the TUI")
+                .long_help(STATUSBAR_LONG_HELP)
+                .value_parser(value_parser!(StatusBar)),
+        )
+        .arg(
+            arg!(--"idle-threshold" <DURATION> "Only move when the system is about to become idle")
+                .long_help(IDLE_THRESHOLD_LONG_HELP)
+                .value_parser(ValueParser::new(parse_interval)),
+        )
+        .arg(
+            arg!(--"idle-margin" <DURATION> "Safety margin before the idle threshold (default: 30 seconds)")
+                .value_parser(ValueParser::new(parse_interval)),
+        )
+        .next_help_heading("Input Options")
+        .arg(
+            arg!(--"poll-ms" <MS> "Milliseconds to wait for key commands between movements (default: 25)")
+                .value_parser(ValueParser::new(parse_millis)),
+        )
+        .arg(
+            arg!(--"debounce-ms" <MS> "Milliseconds of quiet input that end a key command (default: 50)")
+                .long_help(DEBOUNCE_LONG_HELP)
+                .value_parser(ValueParser::new(parse_millis)),
+        )
+        .arg(
+            arg!(--"auto-pause-poll-ms" <MS> "Milliseconds between key checks while auto-paused (default: 80)")
+                .value_parser(ValueParser::new(parse_millis)),
+        )
+        .arg(
+            arg!(--"auto-pause-reset-ms" <MS> "Milliseconds between checks while the mouse is in use (default: 2000)")
+                .value_parser(ValueParser::new(parse_millis)),
+        )
+        .next_help_heading("Hook Options")
+        .arg(arg!(--"on-start" <COMMAND> "Run a shell command on startup").long_help(HOOKS_LONG_HELP))
+        .arg(
+            arg!(--"on-pause" <COMMAND> "Run a shell command when movements are paused")
+                .long_help(HOOKS_LONG_HELP),
+        )
+        .arg(
+            arg!(--"on-resume" <COMMAND> "Run a shell command when movements resume")
+                .long_help(HOOKS_LONG_HELP),
+        )
+        .arg(
+            arg!(--"on-exit" <COMMAND> "Run a shell command on exit")
+                .long_help(HOOKS_LONG_HELP),
+        )
         .next_help_heading("Options")
+        .arg(
+            arg!(--backend <BACKEND> "How cursor movements reach the system")
+                .long_help(BACKEND_LONG_HELP)
+                .value_parser(value_parser!(Backend)),
+        )
+        .arg(
+            arg!(--"inhibit-sleep" "Also ask the OS to keep the system and display awake")
+                .long_help(INHIBIT_SLEEP_LONG_HELP),
+        )
+        .arg(arg!(--"no-check" "Skip the startup check that the cursor can be moved"))
+        .arg(
+            arg!(--"simulate-user" "Randomly push the cursor away to test auto-pausing")
+                .long_help(SIMULATE_USER_LONG_HELP),
+        )
+        .arg(
+            arg!(--seed <SEED> "Seed the random number generator for reproducible movements")
+                .long_help(SEED_LONG_HELP)
+                .value_parser(value_parser!(u64)),
+        )
         .arg(arg!(-h --help "Print help information and quit").action(ArgAction::Help))
-        .arg(arg!(-V --version "Print version information and quit").action(ArgAction::Version))
+        .arg(arg!(-V --version "Print version information and quit").action(ArgAction::Version));
+
+    CONFLICTS.iter().fold(cmd, |cmd, &(a, b)| {
+        cmd.mut_arg(a, |arg| arg.conflicts_with(b))
+    })
 }
 
 pub fn parse_interval(s: &str) -> Result<Duration> {
@@ -102,7 +1061,61 @@ pub fn parse_interval(s: &str) -> Result<Duration> {
         return Ok(result);
     }
 
-    Err(anyhow!("could not parse input as an interval"))
+    parse_with_units(s).map_err(|e| anyhow!("could not parse input as an interval ({e})"))
+}
+
+pub fn parse_duration_range(s: &str) -> Result<DurationRange> {
+    let Some((min, max)) = s.split_once("..") else {
+        return parse_interval(s).map(DurationRange::from);
+    };
+
+    let min = parse_interval(min)?;
+    let max = parse_interval(max)?;
+    ensure!(min <= max, "range start must not be greater than range end");
+
+    Ok(DurationRange::new(min, max))
+}
+
+/// Parses durations written as numbers with units, like `1m30s` or `500ms`.
+///
+/// Spaces are allowed between parts, and between a number and its unit.
+fn parse_with_units(s: &str) -> Result<Duration> {
+    let mut rest = s.trim();
+    ensure!(!rest.is_empty(), "no duration given");
+
+    let mut total = Duration::ZERO;
+    while !rest.is_empty() {
+        let num_len = rest
+            .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+            .unwrap_or(rest.len());
+        ensure!(num_len > 0, "expected a number at '{rest}'");
+        let (value, after) = rest.split_at(num_len);
+        let after = after.trim_start();
+        let unit_len = after
+            .find(|c: char| !c.is_ascii_alphabetic())
+            .unwrap_or(after.len());
+        let unit = &after[..unit_len];
+
+        let value = value
+            .parse::<f64>()
+            .map_err(|_| anyhow!("invalid number '{value}'"))?;
+        let secs = match unit {
+            "ms" => value / 1000.,
+            "s" => value,
+            "m" => value * 60.,
+            "h" => value * 3600.,
+            "" => return Err(anyhow!("missing unit after {value}")),
+            _ => return Err(anyhow!("unknown unit '{unit}'")),
+        };
+        total = Duration::try_from_secs_f64(secs)
+            .ok()
+            .and_then(|duration| total.checked_add(duration))
+            .ok_or_else(|| anyhow!("duration is too long"))?;
+        rest = after[unit_len..].trim_start();
+    }
+
+    ensure!(total > Duration::ZERO, "interval must be a positive number");
+    Ok(total)
 }
 
 fn parse_sec_u64(s: &str) -> Result<Duration> {
@@ -119,13 +1132,126 @@ fn parse_sec_f64(s: &str) -> Result<Duration> {
     match s.parse::<f64>() {
         Ok(value) => {
             ensure!(value > 0., "interval must be a positive number");
-            let ms = value * 1000.;
-            Ok(Duration::from_millis(ms.round() as u64))
+            ensure!(value.is_finite(), "interval must be a finite number");
+            // anything shorter would round down to no interval at all
+            let ms = (value * 1000.).round();
+            ensure!(ms >= 1., "interval must be at least 1ms");
+            Ok(Duration::from_millis(ms as u64))
         }
         Err(e) => Err(anyhow!(e)),
     }
 }
 
+pub fn parse_time(s: &str) -> Result<NaiveTime> {
+    NaiveTime::parse_from_str(s.trim(), "%H:%M")
+        .map_err(|e| anyhow!("could not parse input as a time of day, expected HH:MM ({e})"))
+}
+
+pub fn parse_hours(s: &str) -> Result<Hours> {
+    let (start, end) = s
+        .split_once('-')
+        .ok_or_else(|| anyhow!("expected a range of times, like 09:00-17:00"))?;
+    let hours = Hours {
+        start: parse_time(start)?,
+        end: parse_time(end)?,
+    };
+    ensure!(hours.start != hours.end, "start and end times must differ");
+
+    Ok(hours)
+}
+
+/// Parses days of the week as a list of days or ranges of days, like
+/// `mon-fri` or `mon,wed,fri`.
+pub fn parse_days(s: &str) -> Result<Days> {
+    let parse_day = |s: &str| {
+        s.trim()
+            .parse::<Weekday>()
+            .map_err(|_| anyhow!("unknown day of the week '{}'", s.trim()))
+    };
+
+    let mut days = Days::default();
+    for part in s.split(',') {
+        let Some((first, last)) = part.split_once('-') else {
+            days.insert(parse_day(part)?);
+            continue;
+        };
+
+        // ranges may wrap around the end of the week (e.g., fri-mon)
+        let (mut day, last) = (parse_day(first)?, parse_day(last)?);
+        days.insert(day);
+        while day != last {
+            day = day.succ();
+            days.insert(day);
+        }
+    }
+
+    Ok(days)
+}
+
+fn parse_millis(s: &str) -> Result<Duration> {
+    match s.parse::<u64>() {
+        Ok(value) => {
+            ensure!(value > 0, "milliseconds must be a positive number");
+            Ok(Duration::from_millis(value))
+        }
+        Err(e) => Err(anyhow!(e)),
+    }
+}
+
+fn parse_percent(s: &str) -> Result<f64> {
+    let value = s.trim_end_matches('%').parse::<f64>()?;
+    check_f64("percentage", value, (Excluded(0.), Excluded(100.)))
+}
+
+/// Limits for numbers that must be greater than zero.
+pub const POSITIVE: (Bound<f64>, Bound<f64>) = (Excluded(0.), Unbounded);
+
+/// Returns a parser for a number named `name` that must be within `range`.
+fn parse_f64_in<R>(
+    name: &'static str,
+    range: R,
+) -> impl Fn(&str) -> Result<f64> + Clone + Send + Sync + 'static
+where
+    R: RangeBounds<f64> + Clone + Send + Sync + 'static,
+{
+    move |s| check_f64(name, s.parse::<f64>()?, range.clone())
+}
+
+/// Checks that `value` is within `range`, so options are held to the same
+/// limits whether they come from the command line or a config file.
+pub fn check_f64(name: &str, value: f64, range: impl RangeBounds<f64>) -> Result<f64> {
+    if value.is_finite() && range.contains(&value) {
+        return Ok(value);
+    }
+
+    let limits = match (range.start_bound(), range.end_bound()) {
+        (Excluded(min), Unbounded) if *min == 0. => "a positive number".to_string(),
+        (Included(min), Unbounded) if *min == 0. => "zero or a positive number".to_string(),
+        (Included(min), Unbounded) => format!("at least {min}"),
+        (Included(min), Included(max)) => format!("between {min} and {max} (inclusive)"),
+        (Excluded(min), Excluded(max)) => format!("between {min} and {max} (exclusive)"),
+        _ => "in range".to_string(),
+    };
+    bail!("{name} must be {limits}")
+}
+
+fn parse_size(s: &str) -> Result<(u32, u32)> {
+    let (width, height) = s
+        .split_once(['x', 'X'])
+        .ok_or_else(|| anyhow!("expected a size as WIDTHxHEIGHT (e.g., 800x600)"))?;
+    let width = width
+        .trim()
+        .parse::<u32>()
+        .map_err(|e| anyhow!("invalid width ({e})"))?;
+    let height = height
+        .trim()
+        .parse::<u32>()
+        .map_err(|e| anyhow!("invalid height ({e})"))?;
+    ensure!(width > 0 && height > 0, "size must be at least 1x1");
+
+    Ok((width, height))
+}
+
 fn parse_fps(s: &str) -> Result<u32> {
     // parse first as i64 so we can report better error messages
     match s.parse::<i64>() {
@@ -140,3 +1266,81 @@ fn parse_fps(s: &str) -> Result<u32> {
         Err(e) => Err(anyhow!(e)),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn durations_with_units() {
+        let secs = Duration::from_secs;
+        assert_eq!(parse_with_units("90s").unwrap(), secs(90));
+        assert_eq!(parse_with_units("1m30s").unwrap(), secs(90));
+        assert_eq!(parse_with_units("1m 30s").unwrap(), secs(90));
+        assert_eq!(
+            parse_with_units("500ms").unwrap(),
+            Duration::from_millis(500)
+        );
+        assert_eq!(parse_with_units("2h").unwrap(), secs(7200));
+        assert_eq!(
+            parse_with_units("1.5s").unwrap(),
+            Duration::from_millis(1500)
+        );
+        assert_eq!(parse_with_units("5 s").unwrap(), secs(5));
+    }
+
+    #[test]
+    fn invalid_durations_with_units() {
+        assert!(parse_with_units("").is_err());
+        assert!(parse_with_units("-5s").is_err());
+        assert!(parse_with_units("1.2.3s").is_err());
+        assert!(parse_with_units("0s").is_err());
+        assert!(parse_with_units("0m0s").is_err());
+        assert!(parse_with_units("10x").is_err());
+        assert!(parse_with_units("10").is_err());
+        assert!(parse_with_units("s").is_err());
+        assert!(parse_with_units("99999999999999999999h").is_err());
+        assert!(parse_with_units("18446744073709549568s 1h").is_err());
+    }
+
+    #[test]
+    fn fractional_seconds() {
+        assert_eq!(parse_sec_f64("1.5").unwrap(), Duration::from_millis(1500));
+        assert_eq!(parse_sec_f64("0.001").unwrap(), Duration::from_millis(1));
+        assert!(parse_sec_f64("0.0004").is_err());
+        assert!(parse_sec_f64("0").is_err());
+        assert!(parse_sec_f64("-1.5").is_err());
+        assert!(parse_sec_f64("inf").is_err());
+        assert!(parse_sec_f64("NaN").is_err());
+    }
+
+    #[test]
+    fn intervals_accept_every_format() {
+        assert_eq!(parse_interval("30").unwrap(), Duration::from_secs(30));
+        assert_eq!(parse_interval("0.5").unwrap(), Duration::from_millis(500));
+        assert_eq!(parse_interval("1m30s").unwrap(), Duration::from_secs(90));
+        assert!(parse_interval("0.0004").is_err());
+    }
+
+    #[test]
+    fn duration_ranges() {
+        let range =
+            |min, max| DurationRange::new(Duration::from_millis(min), Duration::from_millis(max));
+        assert_eq!(parse_duration_range("5..30").unwrap(), range(5000, 30000));
+        assert_eq!(parse_duration_range("0.5..2").unwrap(), range(500, 2000));
+        assert_eq!(
+            parse_duration_range("1m..90s").unwrap(),
+            range(60000, 90000)
+        );
+        assert_eq!(parse_duration_range("5..5").unwrap(), range(5000, 5000));
+        assert_eq!(parse_duration_range("5").unwrap(), range(5000, 5000));
+    }
+
+    #[test]
+    fn invalid_duration_ranges() {
+        assert!(parse_duration_range("30..5").is_err());
+        assert!(parse_duration_range("..5").is_err());
+        assert!(parse_duration_range("5..").is_err());
+        assert!(parse_duration_range("0..5").is_err());
+    }
+}
@@ -1,12 +1,956 @@
-use crate::bounds::Bounds;
+use crate::animation::Easing;
+use crate::backend::Backend;
+use crate::bounds::{Axis, Bounds, Region};
+use crate::cli;
+use crate::display::Units;
+use crate::pattern::Pattern;
+use crate::range::DurationRange;
+use crate::schedule::{Cron, Days, Hours, Schedule};
+use crate::session::OnLock;
 
+use anyhow::{anyhow, bail, ensure, Result};
+use chrono::NaiveTime;
+use clap::parser::ValueSource;
+use clap::ArgMatches;
+use serde::de::Error;
+use serde::{Deserialize, Deserializer};
+use toml::{Table, Value};
+use toml_edit::{DocumentMut, Item};
+
+use std::collections::BTreeMap;
+use std::fs::{self, OpenOptions};
+use std::io::{ErrorKind, Write};
+use std::ops::Bound::{Excluded, Included, Unbounded};
+use std::path::{Path, PathBuf};
 use std::time::Duration;
 
+#[derive(Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
 pub struct Config {
-    pub interval: Duration,
+    #[serde(deserialize_with = "de_duration_range")]
+    pub interval: DurationRange,
+    pub jitter: Option<f64>,
+    #[serde(deserialize_with = "de_duration")]
     pub pause_interval: Duration,
     pub fps: u32,
     pub bounds: Bounds,
+    pub bounds_presets: BTreeMap<String, Bounds>,
+    #[serde(deserialize_with = "de_opt_pattern")]
+    pub pattern: Option<Pattern>,
+    pub gaussian: Option<f64>,
+    pub avoid_radius: Option<u32>,
+    pub avoid_count: usize,
+    pub waypoints: Option<PathBuf>,
+    pub shuffle_waypoints: bool,
+    pub max_distance: Option<u32>,
+    pub grid: Option<u32>,
+    pub micro: bool,
+    pub clamp_back: bool,
+    pub drift: Option<f64>,
+    pub wrap: bool,
+    #[serde(rename = "return")]
+    pub return_to_anchor: bool,
     pub animate: bool,
     pub auto_pause: bool,
+    pub chain: bool,
+    pub max_accel: Option<f64>,
+    #[serde(deserialize_with = "de_opt_duration")]
+    pub move_duration: Option<Duration>,
+    pub speed: Option<f64>,
+    pub fitts: Option<f64>,
+    #[serde(deserialize_with = "de_easing")]
+    pub easing: Easing,
+    pub wobble: f64,
+    pub curve: f64,
+    pub spline: bool,
+    pub resume: bool,
+    pub paused: bool,
+    pub stay_on_screen: bool,
+    pub monitor: Option<String>,
+    pub margin: Option<u32>,
+    pub exclude: Vec<Region>,
+    pub lock_axis: Option<Axis>,
+    pub units: Units,
+    pub backend: Backend,
+    pub inhibit_sleep: bool,
+    #[serde(deserialize_with = "de_opt_duration")]
+    pub idle_threshold: Option<Duration>,
+    #[serde(deserialize_with = "de_duration")]
+    pub idle_margin: Duration,
+    #[serde(deserialize_with = "de_opt_duration_range")]
+    pub dwell: Option<DurationRange>,
+    pub dwell_jitter: Option<f64>,
+    #[serde(deserialize_with = "de_opt_duration")]
+    pub start_after: Option<Duration>,
+    #[serde(deserialize_with = "de_opt_duration")]
+    pub daily_limit: Option<Duration>,
+    #[serde(deserialize_with = "de_opt_duration")]
+    pub duration: Option<Duration>,
+    pub count: Option<u64>,
+    #[serde(deserialize_with = "de_opt_time")]
+    pub until: Option<NaiveTime>,
+    pub until_pause: bool,
+    #[serde(deserialize_with = "de_opt_hours")]
+    pub active_hours: Option<Hours>,
+    #[serde(deserialize_with = "de_opt_days")]
+    pub active_days: Option<Days>,
+    #[serde(deserialize_with = "de_opt_cron")]
+    pub cron: Option<Cron>,
+    pub watchdog: Option<String>,
+    #[serde(deserialize_with = "de_duration")]
+    pub watchdog_interval: Duration,
+    pub on_lock: OnLock,
+    pub on_start: Option<String>,
+    pub on_pause: Option<String>,
+    pub on_resume: Option<String>,
+    pub on_exit: Option<String>,
+    pub seed: Option<u64>,
+    pub input: InputConfig,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            interval: Duration::from_secs(1).into(),
+            jitter: None,
+            pause_interval: Duration::from_secs(60),
+            fps: 60,
+            bounds: Bounds::default(),
+            bounds_presets: BTreeMap::new(),
+            pattern: None,
+            gaussian: None,
+            avoid_radius: None,
+            avoid_count: 5,
+            waypoints: None,
+            shuffle_waypoints: false,
+            max_distance: None,
+            grid: None,
+            micro: false,
+            clamp_back: false,
+            drift: None,
+            wrap: false,
+            return_to_anchor: false,
+            animate: true,
+            auto_pause: true,
+            chain: false,
+            max_accel: None,
+            move_duration: None,
+            speed: None,
+            fitts: None,
+            easing: Easing::default(),
+            wobble: 0.,
+            curve: 0.,
+            spline: false,
+            resume: false,
+            paused: false,
+            stay_on_screen: false,
+            monitor: None,
+            margin: None,
+            exclude: vec![],
+            lock_axis: None,
+            units: Units::default(),
+            backend: Backend::default(),
+            inhibit_sleep: false,
+            idle_threshold: None,
+            idle_margin: Duration::from_secs(30),
+            dwell: None,
+            dwell_jitter: None,
+            start_after: None,
+            daily_limit: None,
+            duration: None,
+            count: None,
+            until: None,
+            until_pause: false,
+            active_hours: None,
+            active_days: None,
+            cron: None,
+            watchdog: None,
+            watchdog_interval: Duration::from_secs(30),
+            on_lock: OnLock::default(),
+            on_start: None,
+            on_pause: None,
+            on_resume: None,
+            on_exit: None,
+            seed: None,
+            input: InputConfig::default(),
+        }
+    }
+}
+
+impl Config {
+    /// Builds the configuration from the file given with `--config` (or the
+    /// one found in the platform config directory), then overrides it with
+    /// any options given on the command line.
+    pub fn load(matches: &ArgMatches) -> Result<Self> {
+        let profile = matches.get_one::<String>("profile").map(String::as_str);
+        let path = match matches.get_one::<PathBuf>("config") {
+            Some(path) => Some(path.clone()),
+            None => discover(),
+        };
+        let mut config = match path {
+            Some(path) => Self::from_file(&path, profile)?,
+            None => match profile {
+                Some(name) => bail!("profile '{name}' requires a config file"),
+                None => Self::default(),
+            },
+        };
+        config.merge_matches(matches);
+        if is_explicit(matches, "weights") {
+            let weights = matches
+                .get_many::<u32>("weights")
+                .expect("values should be required by clap")
+                .copied()
+                .collect::<Vec<_>>();
+            config.bounds = config.bounds.with_weights(&weights)?;
+        }
+        if let Some(&inset) = get_explicit::<u32>(matches, "inset") {
+            config.bounds = config.bounds.with_inset(inset)?;
+        }
+        config.validate(matches)?;
+
+        Ok(config)
+    }
+
+    /// Loads a config file, applying the named profile (if any) over the
+    /// `[default]` section.
+    ///
+    /// Options outside of any section are treated as part of `[default]`.
+    pub fn from_file(path: &Path, profile: Option<&str>) -> Result<Self> {
+        let contents = fs::read_to_string(path)
+            .map_err(|e| anyhow!("failed to read config file {} ({e})", path.display()))?;
+        let mut table: Table = toml::from_str(&contents)
+            .map_err(|e| anyhow!("failed to parse config file {} ({e})", path.display()))?;
+
+        let default = take_table(&mut table, "default")?;
+        let mut profiles = take_table(&mut table, "profile")?;
+        merge_tables(&mut table, default);
+        if let Some(name) = profile {
+            let Some(Value::Table(overrides)) = profiles.remove(name) else {
+                bail!("profile '{name}' not found in {}", path.display());
+            };
+            merge_tables(&mut table, overrides);
+        }
+
+        Value::Table(table)
+            .try_into()
+            .map_err(|e| anyhow!("failed to parse config file {} ({e})", path.display()))
+    }
+
+    /// Applies the limits and conflicts that the command line enforces with
+    /// clap, which values from a config file would otherwise skip. This runs
+    /// on the final configuration, so options split between a config file
+    /// and the command line are checked together.
+    fn validate(&self, matches: &ArgMatches) -> Result<()> {
+        let numbers = [
+            ("jitter", self.jitter, (Excluded(0.), Excluded(100.))),
+            ("gaussian", self.gaussian, (Included(1.), Unbounded)),
+            ("drift", self.drift, cli::POSITIVE),
+            ("speed", self.speed, cli::POSITIVE),
+            ("fitts", self.fitts, cli::POSITIVE),
+            ("max-accel", self.max_accel, cli::POSITIVE),
+            ("dwell-jitter", self.dwell_jitter, cli::POSITIVE),
+            ("wobble", Some(self.wobble), (Included(0.), Unbounded)),
+            ("curve", Some(self.curve), (Included(0.), Included(1.))),
+        ];
+        for (name, value, range) in numbers {
+            if let Some(value) = value {
+                cli::check_f64(name, value, range)?;
+            }
+        }
+
+        let counts = [
+            ("fps", Some(self.fps as u64)),
+            ("avoid-radius", self.avoid_radius.map(u64::from)),
+            ("avoid-count", Some(self.avoid_count as u64)),
+            ("max-distance", self.max_distance.map(u64::from)),
+            ("grid", self.grid.map(u64::from)),
+            ("count", self.count),
+        ];
+        for (name, value) in counts {
+            ensure!(value != Some(0), "{name} must be at least 1");
+        }
+
+        for &(a, b) in cli::CONFLICTS {
+            ensure!(
+                !(self.is_set(a, matches) && self.is_set(b, matches)),
+                "{} can't be used with {}",
+                option_name(a),
+                option_name(b)
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Returns `true` if the option with argument id `id` is in effect,
+    /// whether it came from a config file or the command line. Options that
+    /// only exist on the command line are looked up in `matches`.
+    fn is_set(&self, id: &str, matches: &ArgMatches) -> bool {
+        let bounds = &self.bounds;
+        match id {
+            // captured bounds replace whichever ones were configured
+            "absolute-bounds" | "relative-bounds" | "center" | "active-window" | "window"
+                if is_explicit(matches, "capture-bounds") =>
+            {
+                false
+            }
+            "absolute-bounds" => matches!(bounds, Bounds::Rect { .. } | Bounds::Regions(_)),
+            "relative-bounds" => matches!(bounds, Bounds::Relative { .. }),
+            "center" => matches!(bounds, Bounds::Center { .. }),
+            "active-window" => matches!(bounds, Bounds::ActiveWindow { .. }),
+            "window" => matches!(bounds, Bounds::Window { .. }),
+            "stay-on-screen" => self.stay_on_screen,
+            "lock-axis" => self.lock_axis.is_some(),
+            "pattern" => self.pattern.is_some(),
+            "gaussian" => self.gaussian.is_some(),
+            "avoid-radius" => self.avoid_radius.is_some(),
+            "waypoints" => self.waypoints.is_some(),
+            "drift" => self.drift.is_some(),
+            "micro" => self.micro,
+            "return" => self.return_to_anchor,
+            "clamp-back" => self.clamp_back,
+            "max-distance" => self.max_distance.is_some(),
+            "dwell-jitter" => self.dwell_jitter.is_some(),
+            "no-autopause" => !self.auto_pause,
+            "no-animate" => !self.animate,
+            // these always have a value, so only a changed one counts
+            "pause-interval" => self.pause_interval != Self::default().pause_interval,
+            "fps" => self.fps != Self::default().fps,
+            "easing" => self.easing != Easing::default(),
+            "wobble" => self.wobble > 0.,
+            "curve" => self.curve > 0.,
+            "move-duration" => self.move_duration.is_some(),
+            "speed" => self.speed.is_some(),
+            "fitts" => self.fitts.is_some(),
+            "spline" => self.spline,
+            "chain" => self.chain,
+            "max-accel" => self.max_accel.is_some(),
+            "resume" => self.resume,
+            "paused" => self.paused,
+            "count" => self.count.is_some(),
+            _ => is_explicit(matches, id),
+        }
+    }
+
+    /// Overrides values with options that were explicitly given on the
+    /// command line.
+    fn merge_matches(&mut self, matches: &ArgMatches) {
+        if let Some(interval) = get_explicit::<DurationRange>(matches, "INTERVAL") {
+            self.interval = interval;
+        }
+        if let Some(jitter) = get_explicit::<f64>(matches, "jitter") {
+            self.jitter = Some(jitter);
+        }
+        if let Some(pause_interval) = get_explicit::<Duration>(matches, "pause-interval") {
+            self.pause_interval = pause_interval;
+        }
+        if let Some(fps) = get_explicit::<u32>(matches, "fps") {
+            self.fps = fps;
+        }
+        if [
+            "absolute-bounds",
+            "relative-bounds",
+            "active-window",
+            "window",
+        ]
+        .iter()
+        .any(|id| is_explicit(matches, id))
+        {
+            self.bounds = Bounds::from(matches);
+        }
+        if let Some(&(width, height)) = get_explicit::<(u32, u32)>(matches, "center") {
+            self.bounds = Bounds::Center { width, height };
+        }
+        if let Some(pattern) = get_explicit::<Pattern>(matches, "pattern") {
+            self.pattern = Some(pattern);
+        }
+        if let Some(sigma) = get_explicit::<f64>(matches, "gaussian") {
+            self.gaussian = Some(sigma);
+        }
+        if let Some(avoid_radius) = get_explicit::<u32>(matches, "avoid-radius") {
+            self.avoid_radius = Some(avoid_radius);
+        }
+        if let Some(avoid_count) = get_explicit::<usize>(matches, "avoid-count") {
+            self.avoid_count = avoid_count;
+        }
+        if let Some(waypoints) = get_explicit::<PathBuf>(matches, "waypoints") {
+            self.waypoints = Some(waypoints);
+        }
+        if matches.get_flag("shuffle-waypoints") {
+            self.shuffle_waypoints = true;
+        }
+        if let Some(monitor) = get_explicit::<String>(matches, "monitor") {
+            self.monitor = Some(monitor);
+        }
+        if let Some(margin) = get_explicit::<u32>(matches, "margin") {
+            self.margin = Some(margin);
+        }
+        if is_explicit(matches, "exclude") {
+            self.exclude = matches
+                .get_occurrences::<i32>("exclude")
+                .expect("values should be required by clap")
+                .map(|coords| {
+                    let coords = coords.copied().collect::<Vec<i32>>();
+                    Region::from([coords[0], coords[1], coords[2], coords[3]])
+                })
+                .collect();
+        }
+        if let Some(axis) = get_explicit::<Axis>(matches, "lock-axis") {
+            self.lock_axis = Some(axis);
+        }
+        if let Some(units) = get_explicit::<Units>(matches, "units") {
+            self.units = units;
+        }
+        if let Some(backend) = get_explicit::<Backend>(matches, "backend") {
+            self.backend = backend;
+        }
+        if matches.get_flag("inhibit-sleep") {
+            self.inhibit_sleep = true;
+        }
+        if let Some(grid) = get_explicit::<u32>(matches, "grid") {
+            self.grid = Some(grid);
+        }
+        if let Some(max_distance) = get_explicit::<u32>(matches, "max-distance") {
+            self.max_distance = Some(max_distance);
+        }
+        if let Some(drift) = get_explicit::<f64>(matches, "drift") {
+            self.drift = Some(drift);
+        }
+        if matches.get_flag("wrap") {
+            self.wrap = true;
+        }
+        if matches.get_flag("micro") {
+            self.micro = true;
+        }
+        if matches.get_flag("clamp-back") {
+            self.clamp_back = true;
+        }
+        if matches.get_flag("return") {
+            self.return_to_anchor = true;
+        }
+        if matches.get_flag("no-animate") {
+            self.animate = false;
+        }
+        if matches.get_flag("no-autopause") {
+            self.auto_pause = false;
+        }
+        if matches.get_flag("chain") {
+            self.chain = true;
+        }
+        if let Some(max_accel) = get_explicit::<f64>(matches, "max-accel") {
+            self.max_accel = Some(max_accel);
+        }
+        if let Some(move_duration) = get_explicit::<Duration>(matches, "move-duration") {
+            self.move_duration = Some(move_duration);
+        }
+        if let Some(speed) = get_explicit::<f64>(matches, "speed") {
+            self.speed = Some(speed);
+        }
+        if let Some(fitts) = get_explicit::<f64>(matches, "fitts") {
+            self.fitts = Some(fitts);
+        }
+        if let Some(easing) = get_explicit::<Easing>(matches, "easing") {
+            self.easing = easing;
+        }
+        if let Some(wobble) = get_explicit::<f64>(matches, "wobble") {
+            self.wobble = wobble;
+        }
+        if let Some(curve) = get_explicit::<f64>(matches, "curve") {
+            self.curve = curve;
+        }
+        if matches.get_flag("spline") {
+            self.spline = true;
+        }
+        if matches.get_flag("resume") {
+            self.resume = true;
+        }
+        if matches.get_flag("paused") {
+            self.paused = true;
+        }
+        if matches.get_flag("stay-on-screen") {
+            self.stay_on_screen = true;
+        }
+        if let Some(idle_threshold) = get_explicit::<Duration>(matches, "idle-threshold") {
+            self.idle_threshold = Some(idle_threshold);
+        }
+        if let Some(idle_margin) = get_explicit::<Duration>(matches, "idle-margin") {
+            self.idle_margin = idle_margin;
+        }
+        if let Some(dwell) = get_explicit::<DurationRange>(matches, "dwell") {
+            self.dwell = Some(dwell);
+        }
+        if let Some(dwell_jitter) = get_explicit::<f64>(matches, "dwell-jitter") {
+            self.dwell_jitter = Some(dwell_jitter);
+        }
+        if let Some(start_after) = get_explicit::<Duration>(matches, "start-after") {
+            self.start_after = Some(start_after);
+        }
+        if let Some(daily_limit) = get_explicit::<Duration>(matches, "daily-limit") {
+            self.daily_limit = Some(daily_limit);
+        }
+        if let Some(duration) = get_explicit::<Duration>(matches, "duration") {
+            self.duration = Some(duration);
+        }
+        if let Some(count) = get_explicit::<u64>(matches, "count") {
+            self.count = Some(count);
+        }
+        if let Some(until) = get_explicit::<NaiveTime>(matches, "until") {
+            self.until = Some(until);
+        }
+        if matches.get_flag("until-pause") {
+            self.until_pause = true;
+        }
+        if let Some(active_hours) = get_explicit::<Hours>(matches, "active-hours") {
+            self.active_hours = Some(active_hours);
+        }
+        if let Some(active_days) = get_explicit::<Days>(matches, "active-days") {
+            self.active_days = Some(active_days);
+        }
+        if let Some(cron) = get_explicit::<Cron>(matches, "cron") {
+            self.cron = Some(cron);
+        }
+        if let Some(watchdog) = get_explicit::<String>(matches, "watchdog") {
+            self.watchdog = Some(watchdog);
+        }
+        if let Some(watchdog_interval) = get_explicit::<Duration>(matches, "watchdog-interval") {
+            self.watchdog_interval = watchdog_interval;
+        }
+        if let Some(on_lock) = get_explicit::<OnLock>(matches, "on-lock") {
+            self.on_lock = on_lock;
+        }
+        if let Some(on_start) = get_explicit::<String>(matches, "on-start") {
+            self.on_start = Some(on_start);
+        }
+        if let Some(on_pause) = get_explicit::<String>(matches, "on-pause") {
+            self.on_pause = Some(on_pause);
+        }
+        if let Some(on_resume) = get_explicit::<String>(matches, "on-resume") {
+            self.on_resume = Some(on_resume);
+        }
+        if let Some(on_exit) = get_explicit::<String>(matches, "on-exit") {
+            self.on_exit = Some(on_exit);
+        }
+        if let Some(seed) = get_explicit::<u64>(matches, "seed") {
+            self.seed = Some(seed);
+        }
+        if let Some(poll) = get_explicit::<Duration>(matches, "poll-ms") {
+            self.input.poll = poll;
+        }
+        if let Some(debounce) = get_explicit::<Duration>(matches, "debounce-ms") {
+            self.input.debounce = debounce;
+        }
+        if let Some(auto_pause_poll) = get_explicit::<Duration>(matches, "auto-pause-poll-ms") {
+            self.input.auto_pause_poll = auto_pause_poll;
+        }
+        if let Some(auto_pause_reset) = get_explicit::<Duration>(matches, "auto-pause-reset-ms") {
+            self.input.auto_pause_reset = auto_pause_reset;
+        }
+    }
+
+    /// Returns when movements are limited to, if they are.
+    pub fn schedule(&self) -> Option<Schedule> {
+        Schedule::new(self.active_hours, self.active_days, self.cron)
+    }
+
+    /// Returns how much system idle time to wait for before each movement,
+    /// if movements should only fill idle gaps.
+    pub fn idle_target(&self) -> Option<Duration> {
+        self.idle_threshold
+            .map(|threshold| threshold.saturating_sub(self.idle_margin))
+    }
+}
+
+/// Timing used when reading key commands from the terminal.
+#[derive(Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "kebab-case")]
+pub struct InputConfig {
+    /// How long to wait for a key command between movements.
+    #[serde(rename = "poll-ms", deserialize_with = "de_millis")]
+    pub poll: Duration,
+    /// How long input must be quiet before a key command is considered done.
+    #[serde(rename = "debounce-ms", deserialize_with = "de_millis")]
+    pub debounce: Duration,
+    /// How often to check for key commands while counting down an auto-pause.
+    #[serde(rename = "auto-pause-poll-ms", deserialize_with = "de_millis")]
+    pub auto_pause_poll: Duration,
+    /// How long to wait before checking again while the mouse is still being
+    /// used during an auto-pause.
+    #[serde(rename = "auto-pause-reset-ms", deserialize_with = "de_millis")]
+    pub auto_pause_reset: Duration,
+}
+
+impl Default for InputConfig {
+    fn default() -> Self {
+        Self {
+            poll: Duration::from_millis(25),
+            debounce: Duration::from_millis(50),
+            auto_pause_poll: Duration::from_millis(80),
+            auto_pause_reset: Duration::from_millis(2000),
+        }
+    }
+}
+
+/// A fully commented config file with every option set to its default.
+const EXAMPLE: &str = include_str!("config.example.toml");
+
+/// Handles the `config` subcommand.
+pub fn run(matches: &ArgMatches) -> Result<()> {
+    match matches.subcommand() {
+        Some(("path", _)) => {
+            match discover() {
+                Some(path) => println!("{}", path.display()),
+                None => match default_path() {
+                    Some(path) => println!("no config file found (expected {})", path.display()),
+                    None => println!("no config directory on this platform"),
+                },
+            }
+            Ok(())
+        }
+        Some(("init", sub_matches)) => {
+            let path = match sub_matches.get_one::<PathBuf>("PATH") {
+                Some(path) => path.clone(),
+                None => {
+                    default_path().ok_or_else(|| anyhow!("no config directory on this platform"))?
+                }
+            };
+            init(&path, sub_matches.get_flag("force"))?;
+            println!("wrote example config to {}", path.display());
+            Ok(())
+        }
+        _ => unreachable!("subcommand should be required by clap"),
+    }
+}
+
+/// Writes the example config to `path`, refusing to replace an existing file
+/// unless `force` is set.
+fn init(path: &Path, force: bool) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    let mut options = OpenOptions::new();
+    options.write(true);
+    if force {
+        options.create(true).truncate(true);
+    } else {
+        options.create_new(true);
+    }
+
+    let mut file = options.open(path).map_err(|e| match e.kind() {
+        ErrorKind::AlreadyExists => anyhow!(
+            "{} already exists (use --force to overwrite it)",
+            path.display()
+        ),
+        _ => anyhow!("failed to create {} ({e})", path.display()),
+    })?;
+    file.write_all(EXAMPLE.as_bytes())?;
+
+    Ok(())
+}
+
+/// Writes `[profile.NAME]` with the options in `body` to the config file at
+/// `path` (or the default one), replacing the profile if it's already there
+/// and creating the file if needed. Returns the path that was written.
+pub fn save_profile(path: Option<&Path>, name: &str, body: &str) -> Result<PathBuf> {
+    let path = match path {
+        Some(path) => path.to_path_buf(),
+        None => default_path().ok_or_else(|| anyhow!("no config directory on this platform"))?,
+    };
+    let contents = match fs::read_to_string(&path) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == ErrorKind::NotFound => String::new(),
+        Err(e) => bail!("failed to read config file {} ({e})", path.display()),
+    };
+
+    // edit the document in place, so comments and formatting elsewhere in
+    // the file are kept
+    let mut doc = contents
+        .parse::<DocumentMut>()
+        .map_err(|e| anyhow!("failed to parse config file {} ({e})", path.display()))?;
+    let options = body
+        .parse::<DocumentMut>()
+        .map_err(|e| anyhow!("invalid options for profile '{name}' ({e})"))?;
+
+    let profiles = doc
+        .entry("profile")
+        .or_insert_with(|| {
+            let mut profiles = toml_edit::Table::new();
+            // only `[profile.NAME]` headers are written, not `[profile]`
+            profiles.set_implicit(true);
+            Item::Table(profiles)
+        })
+        .as_table_mut()
+        .ok_or_else(|| anyhow!("'profile' must be a section in {}", path.display()))?;
+    let profile = profiles
+        .entry(name)
+        .or_insert_with(toml_edit::table)
+        .as_table_mut()
+        .ok_or_else(|| anyhow!("'profile.{name}' must be a section in {}", path.display()))?;
+    // replace the old options (and any nested sections) entirely
+    profile.clear();
+    for (key, item) in options.iter() {
+        profile.insert(key, item.clone());
+    }
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(&path, doc.to_string())
+        .map_err(|e| anyhow!("failed to write config file {} ({e})", path.display()))?;
+
+    Ok(path)
+}
+
+/// Returns where the config file is expected to be when `--config` isn't
+/// given (e.g., `~/.config/mouse-jiggler/config.toml` on Linux).
+pub fn default_path() -> Option<PathBuf> {
+    dirs::config_dir().map(|dir| dir.join("mouse-jiggler").join("config.toml"))
+}
+
+/// Returns the default config file, if it exists.
+fn discover() -> Option<PathBuf> {
+    default_path().filter(|path| path.is_file())
+}
+
+/// Removes the section `key` from `table`, returning an empty table if it
+/// doesn't exist.
+fn take_table(table: &mut Table, key: &str) -> Result<Table> {
+    match table.remove(key) {
+        Some(Value::Table(section)) => Ok(section),
+        Some(_) => bail!("'{key}' must be a section"),
+        None => Ok(Table::new()),
+    }
+}
+
+/// Copies all values in `overrides` into `base`.
+///
+/// Nested option tables (e.g., `[input]`) are merged key by key so a profile
+/// only needs to list the values it changes.
+fn merge_tables(base: &mut Table, overrides: Table) {
+    for (key, value) in overrides {
+        match value {
+            Value::Table(nested) if key == "input" || key == "bounds-presets" => {
+                if let Some(Value::Table(base_nested)) = base.get_mut(&key) {
+                    merge_tables(base_nested, nested);
+                } else {
+                    base.insert(key, Value::Table(nested));
+                }
+            }
+            value => {
+                base.insert(key, value);
+            }
+        }
+    }
+}
+
+/// Returns `true` if `id` was given on the command line, as opposed to
+/// coming from a default value.
+/// Returns how an option is written in messages, with the config file
+/// setting for the command line flags that turn something off.
+fn option_name(id: &str) -> String {
+    match id {
+        "no-animate" => "'no-animate' (or 'animate = false')".into(),
+        "no-autopause" => "'no-autopause' (or 'auto-pause = false')".into(),
+        _ => format!("'{id}'"),
+    }
+}
+
+pub fn is_explicit(matches: &ArgMatches, id: &str) -> bool {
+    matches.value_source(id) == Some(ValueSource::CommandLine)
+}
+
+fn get_explicit<T>(matches: &ArgMatches, id: &str) -> Option<T>
+where
+    T: Clone + Send + Sync + 'static,
+{
+    if !is_explicit(matches, id) {
+        return None;
+    }
+
+    matches.get_one::<T>(id).cloned()
+}
+
+/// Durations in config files are either a number of seconds, or a string in
+/// the same format as the command line.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum DurationValue {
+    Seconds(f64),
+    Text(String),
+}
+
+impl DurationValue {
+    fn text(self) -> String {
+        match self {
+            DurationValue::Seconds(secs) => secs.to_string(),
+            DurationValue::Text(text) => text,
+        }
+    }
+}
+
+fn de_duration<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Duration, D::Error> {
+    let value = DurationValue::deserialize(deserializer)?;
+    cli::parse_interval(&value.text()).map_err(D::Error::custom)
+}
+
+fn de_opt_duration<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<Duration>, D::Error> {
+    de_duration(deserializer).map(Some)
+}
+
+fn de_duration_range<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<DurationRange, D::Error> {
+    let value = DurationValue::deserialize(deserializer)?;
+    cli::parse_duration_range(&value.text()).map_err(D::Error::custom)
+}
+
+fn de_opt_duration_range<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<DurationRange>, D::Error> {
+    de_duration_range(deserializer).map(Some)
+}
+
+fn de_opt_time<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<NaiveTime>, D::Error> {
+    let value = String::deserialize(deserializer)?;
+    cli::parse_time(&value).map(Some).map_err(D::Error::custom)
+}
+
+fn de_opt_hours<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<Hours>, D::Error> {
+    let value = String::deserialize(deserializer)?;
+    cli::parse_hours(&value).map(Some).map_err(D::Error::custom)
+}
+
+fn de_opt_days<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<Days>, D::Error> {
+    let value = String::deserialize(deserializer)?;
+    cli::parse_days(&value).map(Some).map_err(D::Error::custom)
+}
+
+fn de_opt_cron<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<Cron>, D::Error> {
+    let value = String::deserialize(deserializer)?;
+    value.parse().map(Some).map_err(D::Error::custom)
+}
+
+fn de_opt_pattern<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<Pattern>, D::Error> {
+    let value = String::deserialize(deserializer)?;
+    value.parse().map(Some).map_err(D::Error::custom)
+}
+
+fn de_easing<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Easing, D::Error> {
+    let value = String::deserialize(deserializer)?;
+    value.parse().map_err(D::Error::custom)
+}
+
+fn de_millis<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Duration, D::Error> {
+    let value = u64::deserialize(deserializer)?;
+    if value == 0 {
+        return Err(D::Error::custom("milliseconds must be a positive number"));
+    }
+
+    Ok(Duration::from_millis(value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_path() -> PathBuf {
+        std::env::temp_dir().join(format!(
+            "mouser-test-{}-{}.toml",
+            std::process::id(),
+            fastrand::u64(..)
+        ))
+    }
+
+    /// Loads `contents` as the config file, with `args` on the command line.
+    fn load(contents: &str, args: &[&str]) -> Result<Config> {
+        let path = temp_path();
+        fs::write(&path, contents)?;
+        let path_arg = path.to_string_lossy().into_owned();
+        let args = ["mouser", "--config", &path_arg]
+            .into_iter()
+            .chain(args.iter().copied());
+        let result = cli::build()
+            .try_get_matches_from(args)
+            .map_err(anyhow::Error::from)
+            .and_then(|matches| Config::load(&matches));
+        let _ = fs::remove_file(&path);
+
+        result
+    }
+
+    /// Saves `body` as the learned profile in a config file starting out as
+    /// `contents`, and returns what the file ends up as.
+    fn save(contents: &str, body: &str) -> Result<String> {
+        let path = temp_path();
+        fs::write(&path, contents)?;
+        let result = save_profile(Some(&path), "learned", body)
+            .and_then(|path| Ok(fs::read_to_string(path)?));
+        let _ = fs::remove_file(&path);
+
+        result
+    }
+
+    #[test]
+    fn file_values_are_range_checked() {
+        assert!(load("speed = 0.0", &[]).is_err());
+        assert!(load("max-accel = 0.0", &[]).is_err());
+        assert!(load("fps = 0", &[]).is_err());
+        assert!(load("curve = 1.5", &[]).is_err());
+        assert!(load("speed = 300.0\nfps = 30", &[]).is_ok());
+    }
+
+    #[test]
+    fn conflicts_within_a_file_are_rejected() {
+        assert!(load("micro = true\nreturn = true", &[]).is_err());
+        assert!(load("speed = 300.0\nanimate = false", &[]).is_err());
+    }
+
+    #[test]
+    fn conflicts_between_file_and_command_line_are_rejected() {
+        assert!(load("speed = 300.0", &["--no-animate"]).is_err());
+        assert!(load("chain = true", &["--curve", "0.5"]).is_err());
+        assert!(load("drift = 5.0", &["--micro"]).is_err());
+        assert!(load("drift = 5.0", &["--count", "3"]).is_err());
+        assert!(load("[default]\nstay-on-screen = true", &["--center", "800x600"]).is_err());
+    }
+
+    #[test]
+    fn unrelated_file_and_command_line_values_combine() {
+        assert!(load("speed = 300.0", &["--chain"]).is_ok());
+        assert!(load("drift = 5.0", &["--wrap"]).is_ok());
+        assert!(load("drift = 5.0", &["--watchdog", "true"]).is_ok());
+        assert!(load("drift = 5.0", &["--cron", "* 9-17 * * 1-5"]).is_ok());
+    }
+
+    #[test]
+    fn saving_a_profile_keeps_the_rest_of_the_file() {
+        let contents = "# mine\nfps = 30\n\n[other] # not a profile\nspeed = 100.0\n";
+        let saved = save(contents, "speed = 250.0\n").unwrap();
+
+        assert!(saved.starts_with(contents));
+        let table = toml::from_str::<Table>(&saved).unwrap();
+        assert_eq!(table["profile"]["learned"]["speed"].as_float(), Some(250.));
+    }
+
+    #[test]
+    fn saving_a_profile_replaces_it() {
+        let contents = "[profile.learned]\nspeed = 1.0\n\n[profile.learned.input]\n\
+                        idle-ms = 500\n\n[ profile . \"fast\" ]\nspeed = 900.0\n";
+        let saved = save(contents, "interval = \"1.00s..2.00s\"\n").unwrap();
+
+        let table = toml::from_str::<Table>(&saved).unwrap();
+        let learned = table["profile"]["learned"].as_table().unwrap();
+        assert_eq!(learned["interval"].as_str(), Some("1.00s..2.00s"));
+        assert!(!learned.contains_key("speed"));
+        assert!(!learned.contains_key("input"));
+        assert_eq!(table["profile"]["fast"]["speed"].as_float(), Some(900.));
+    }
+
+    #[test]
+    fn saving_a_profile_needs_a_profile_section() {
+        assert!(save("profile = 1\n", "speed = 250.0\n").is_err());
+        assert!(save("[profile]\nlearned = 1\n", "speed = 250.0\n").is_err());
+    }
 }
@@ -0,0 +1,67 @@
+/// Returns `true` if the secure desktop (UAC prompt, lock screen, or the
+/// Ctrl+Alt+Del screen) is receiving input instead of the user's desktop.
+///
+/// Input can't be injected while the secure desktop is active, so any
+/// failures during this time are expected and temporary.
+#[cfg(windows)]
+pub fn is_secure_desktop_active() -> bool {
+    use windows_sys::Win32::System::StationsAndDesktops::{
+        CloseDesktop, OpenInputDesktop, DESKTOP_SWITCHDESKTOP,
+    };
+
+    // SAFETY: the handle is checked before use and closed immediately
+    unsafe {
+        let desktop = OpenInputDesktop(0, 0, DESKTOP_SWITCHDESKTOP);
+        if desktop == 0 {
+            return true;
+        }
+        CloseDesktop(desktop);
+    }
+
+    false
+}
+
+#[cfg(not(windows))]
+pub fn is_secure_desktop_active() -> bool {
+    false
+}
+
+/// Makes cursor and display coordinates physical pixels on every display.
+///
+/// Otherwise Windows scales them for the primary display's DPI, so points on
+/// displays with other scale factors land in the wrong place.
+#[cfg(windows)]
+pub fn set_dpi_aware() {
+    use windows_sys::Win32::UI::HiDpi::{
+        SetProcessDpiAwarenessContext, DPI_AWARENESS_CONTEXT_PER_MONITOR_AWARE_V2,
+    };
+
+    // SAFETY: this only changes how coordinates are reported to this process,
+    // and failing (e.g., on older versions of Windows) keeps the default
+    unsafe {
+        SetProcessDpiAwarenessContext(DPI_AWARENESS_CONTEXT_PER_MONITOR_AWARE_V2);
+    }
+}
+
+#[cfg(not(windows))]
+pub fn set_dpi_aware() {}
+
+/// Returns `true` if this process may post input events, which macOS only
+/// allows once the terminal (or binary) is given Accessibility permission.
+///
+/// Without it, events are silently dropped and the cursor never moves.
+#[cfg(target_os = "macos")]
+pub fn is_input_trusted() -> bool {
+    #[link(name = "ApplicationServices", kind = "framework")]
+    extern "C" {
+        fn AXIsProcessTrusted() -> bool;
+    }
+
+    // SAFETY: this only reads the process's permissions
+    unsafe { AXIsProcessTrusted() }
+}
+
+#[cfg(not(target_os = "macos"))]
+pub fn is_input_trusted() -> bool {
+    true
+}
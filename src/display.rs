@@ -0,0 +1,272 @@
+use crate::bounds::Region;
+use crate::mouse::PointExt;
+
+use anyhow::{anyhow, Result};
+use clap::builder::PossibleValue;
+use clap::ValueEnum;
+use display_info::DisplayInfo;
+use serde::Deserialize;
+
+use std::fmt;
+
+/// Units for absolute coordinates given on the command line or in config
+/// files.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Units {
+    /// Pixels of the display, as measured in screenshots.
+    #[default]
+    Physical,
+    /// Pixels divided by the display's scale factor (points on macOS).
+    Logical,
+}
+
+impl Units {
+    /// Returns the units the cursor moves in on this platform.
+    pub fn cursor() -> Self {
+        if cfg!(target_os = "macos") {
+            Units::Logical
+        } else {
+            Units::Physical
+        }
+    }
+}
+
+impl ValueEnum for Units {
+    fn value_variants<'a>() -> &'a [Self] {
+        &[Units::Physical, Units::Logical]
+    }
+
+    fn to_possible_value(&self) -> Option<PossibleValue> {
+        Some(match self {
+            Units::Physical => PossibleValue::new("physical").help("Pixels, as in screenshots"),
+            Units::Logical => PossibleValue::new("logical").help("Pixels scaled for DPI"),
+        })
+    }
+}
+
+/// A connected display and its position in the virtual screen.
+#[derive(Debug, Clone)]
+pub struct Display {
+    pub name: String,
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+    pub scale_factor: f32,
+    pub is_primary: bool,
+}
+
+impl Display {
+    #[inline]
+    pub fn left(&self) -> i32 {
+        self.x
+    }
+
+    #[inline]
+    pub fn top(&self) -> i32 {
+        self.y
+    }
+
+    /// Returns the rightmost pixel column on this display (inclusive).
+    #[inline]
+    pub fn right(&self) -> i32 {
+        self.x + self.width as i32 - 1
+    }
+
+    /// Returns the bottommost pixel row on this display (inclusive).
+    #[inline]
+    pub fn bottom(&self) -> i32 {
+        self.y + self.height as i32 - 1
+    }
+
+    pub fn contains(&self, p: PointExt) -> bool {
+        (self.left()..=self.right()).contains(&p.x) && (self.top()..=self.bottom()).contains(&p.y)
+    }
+
+    pub fn center(&self) -> PointExt {
+        PointExt::new(
+            self.x + self.width as i32 / 2,
+            self.y + self.height as i32 / 2,
+        )
+    }
+
+    pub fn region(&self) -> Region {
+        Region {
+            x1: self.left(),
+            y1: self.top(),
+            x2: self.right(),
+            y2: self.bottom(),
+        }
+    }
+
+    /// Returns how many cursor pixels one pixel in `units` covers on this
+    /// display.
+    pub fn factor(&self, units: Units) -> f64 {
+        match (units, Units::cursor()) {
+            (given, cursor) if given == cursor => 1.,
+            (Units::Physical, _) => 1. / self.scale_factor as f64,
+            (Units::Logical, _) => self.scale_factor as f64,
+        }
+    }
+
+    /// Returns the point `(dx, dy)` from this display's top left corner,
+    /// measured in `units`, in the coordinates the cursor moves in.
+    pub fn at_offset(&self, dx: i32, dy: i32, units: Units) -> PointExt {
+        let factor = self.factor(units);
+        PointExt::new(
+            self.x + (dx as f64 * factor).round() as i32,
+            self.y + (dy as f64 * factor).round() as i32,
+        )
+    }
+
+    /// Returns how far `p` is from this display's top left corner, measured
+    /// in `units`. This is the inverse of [`Display::at_offset`].
+    pub fn offset_of(&self, p: PointExt, units: Units) -> PointExt {
+        let factor = self.factor(units);
+        PointExt::new(
+            ((p.x - self.x) as f64 / factor).round() as i32,
+            ((p.y - self.y) as f64 / factor).round() as i32,
+        )
+    }
+}
+
+impl fmt::Display for Display {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}x{} at {}, {}",
+            self.width, self.height, self.x, self.y
+        )
+    }
+}
+
+impl From<DisplayInfo> for Display {
+    fn from(value: DisplayInfo) -> Self {
+        Self {
+            name: value.name,
+            x: value.x,
+            y: value.y,
+            width: value.width,
+            height: value.height,
+            scale_factor: value.scale_factor,
+            is_primary: value.is_primary,
+        }
+    }
+}
+
+/// Returns the absolute point `p`, measured in `units`, in the coordinates the
+/// cursor moves in.
+///
+/// Points are scaled from the top left corner of the display they are on, so
+/// displays are assumed to start at the same positions in either units.
+/// Points off every display are left as they are.
+pub fn to_cursor(p: PointExt, units: Units, displays: &[Display]) -> PointExt {
+    let on_display = |d: &Display| {
+        let factor = d.factor(units);
+        let (dx, dy) = ((p.x - d.x) as f64, (p.y - d.y) as f64);
+        (0. ..d.width as f64 / factor).contains(&dx)
+            && (0. ..d.height as f64 / factor).contains(&dy)
+    };
+
+    match displays.iter().find(|d| on_display(d)) {
+        Some(d) => d.at_offset(p.x - d.x, p.y - d.y, units),
+        None => p,
+    }
+}
+
+/// Returns the point `p`, in the coordinates the cursor moves in, measured in
+/// `units`. This is the inverse of [`to_cursor`].
+pub fn from_cursor(p: PointExt, units: Units, displays: &[Display]) -> PointExt {
+    match displays.iter().find(|d| d.contains(p)) {
+        Some(d) => {
+            let offset = d.offset_of(p, units);
+            PointExt::new(d.x + offset.x, d.y + offset.y)
+        }
+        None => p,
+    }
+}
+
+/// Returns the display containing `p`.
+pub fn at(p: PointExt) -> Result<Display> {
+    DisplayInfo::from_point(p.x, p.y)
+        .map(Display::from)
+        .map_err(|e| anyhow!("failed to find display at {p} ({e})"))
+}
+
+/// Returns the display selected by `selector`, either its index as listed by
+/// the `displays` subcommand (starting from 1) or its name.
+pub fn find(selector: &str) -> Result<Display> {
+    let displays = all()?;
+    if let Ok(index) = selector.parse::<usize>() {
+        return index
+            .checked_sub(1)
+            .and_then(|i| displays.get(i).cloned())
+            .ok_or_else(|| anyhow!("no display {index} (found {})", displays.len()));
+    }
+
+    displays
+        .into_iter()
+        .find(|d| d.name.eq_ignore_ascii_case(selector))
+        .ok_or_else(|| anyhow!("no display named '{selector}'"))
+}
+
+/// Returns the primary display.
+pub fn primary() -> Result<Display> {
+    all()?
+        .into_iter()
+        .find(|d| d.is_primary)
+        .ok_or_else(|| anyhow!("failed to find the primary display"))
+}
+
+/// Returns the smallest region covering every connected display.
+pub fn desktop() -> Result<Region> {
+    let displays = all()?;
+    if displays.is_empty() {
+        return Err(anyhow!("no displays are connected"));
+    }
+
+    Ok(Region {
+        x1: displays.iter().map(Display::left).min().unwrap_or(0),
+        y1: displays.iter().map(Display::top).min().unwrap_or(0),
+        x2: displays.iter().map(Display::right).max().unwrap_or(0),
+        y2: displays.iter().map(Display::bottom).max().unwrap_or(0),
+    })
+}
+
+/// Returns all connected displays.
+pub fn all() -> Result<Vec<Display>> {
+    DisplayInfo::all()
+        .map(|displays| displays.into_iter().map(Display::from).collect())
+        .map_err(|e| anyhow!("failed to list displays ({e})"))
+}
+
+/// Runs the `displays` subcommand, printing every connected display.
+pub fn run() -> Result<()> {
+    let displays = all()?;
+    let name_width = displays
+        .iter()
+        .map(|d| d.name.len())
+        .max()
+        .unwrap_or(0)
+        .max("NAME".len());
+
+    println!(
+        "{:<3}  {:<name_width$}  {:<11}  {:<5}  ORIGIN",
+        "#", "NAME", "RESOLUTION", "SCALE"
+    );
+    for (i, d) in displays.iter().enumerate() {
+        let primary = if d.is_primary { "  (primary)" } else { "" };
+        println!(
+            "{:<3}  {:<name_width$}  {:<11}  {:<5.2}  {}, {}{primary}",
+            i + 1,
+            d.name,
+            format!("{}x{}", d.width, d.height),
+            d.scale_factor,
+            d.x,
+            d.y,
+        );
+    }
+
+    Ok(())
+}
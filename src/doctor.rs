@@ -0,0 +1,152 @@
+use crate::backend::Backend;
+use crate::config::Config;
+use crate::desktop;
+use crate::display;
+use crate::environment::Environment;
+use crate::mouse::MouseExt;
+
+use anyhow::{bail, Result};
+use clap::ValueEnum;
+
+use std::fmt;
+
+/// The outcome of a single check.
+enum Outcome {
+    Pass(String),
+    Fail(String),
+    Skip(String),
+}
+
+impl fmt::Display for Outcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Outcome::Pass(detail) => write!(f, "pass  {detail}"),
+            Outcome::Fail(detail) => write!(f, "FAIL  {detail}"),
+            Outcome::Skip(detail) => write!(f, "skip  {detail}"),
+        }
+    }
+}
+
+/// Checks whether the cursor can be moved here, printing what passed and
+/// what failed (with hints on fixing it), so users can diagnose a cursor
+/// that doesn't move.
+///
+/// Each backend that exists on this platform is tried, by nudging the cursor
+/// 1px and back, but only the selected one has to work.
+pub fn run(mut config: Config) -> Result<()> {
+    let environment = Environment::detect(&config.bounds, config.backend);
+    for (label, value) in environment.lines() {
+        println!("{:<16}{value}", format!("{label}:"));
+    }
+    println!();
+
+    let mut checks = vec![
+        ("displays", displays()),
+        ("accessibility", accessibility()),
+        ("input desktop", input_desktop()),
+        ("/dev/uinput", uinput()),
+    ];
+    let selected = config.backend;
+    let mut works = false;
+    for &backend in Backend::value_variants() {
+        config.backend = backend;
+        let outcome = check_backend(&config);
+        // a backend that started but can't be verified is given the benefit
+        // of the doubt
+        works |=
+            backend == selected && backend.is_available() && !matches!(outcome, Outcome::Fail(_));
+        checks.push((backend.name(), outcome));
+    }
+
+    let width = checks.iter().map(|(name, _)| name.len()).max().unwrap_or(0);
+    for (name, outcome) in &checks {
+        println!("{name:<width$}  {outcome}");
+    }
+
+    // other backends failing is expected (e.g., without permissions for
+    // them), as long as the one that would be used works
+    if !works {
+        println!();
+        bail!(
+            "the selected backend '{}' can't move the cursor, try one that passed with \
+             '--backend'",
+            selected.name()
+        );
+    }
+
+    Ok(())
+}
+
+fn displays() -> Outcome {
+    match display::all() {
+        Ok(displays) if displays.is_empty() => Outcome::Fail("no displays were found".into()),
+        Ok(displays) => Outcome::Pass(format!("{} found", displays.len())),
+        Err(e) => Outcome::Fail(format!("failed to list displays ({e})")),
+    }
+}
+
+fn accessibility() -> Outcome {
+    if !cfg!(target_os = "macos") {
+        return Outcome::Skip("only needed on macOS".into());
+    }
+
+    if desktop::is_input_trusted() {
+        Outcome::Pass("allowed to control the mouse".into())
+    } else {
+        Outcome::Fail(
+            "not allowed to control the mouse, turn on the app running mouser in System \
+             Settings > Privacy & Security > Accessibility"
+                .into(),
+        )
+    }
+}
+
+fn input_desktop() -> Outcome {
+    if !cfg!(windows) {
+        return Outcome::Skip("only applies to Windows".into());
+    }
+
+    if desktop::is_secure_desktop_active() {
+        Outcome::Fail("the secure desktop (UAC or lock screen) is blocking input".into())
+    } else {
+        Outcome::Pass("the user's desktop is receiving input".into())
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn uinput() -> Outcome {
+    match crate::backend::check_uinput() {
+        Ok(_) => Outcome::Pass("writable".into()),
+        Err(e) => Outcome::Fail(e.to_string()),
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+fn uinput() -> Outcome {
+    Outcome::Skip("only applies to Linux".into())
+}
+
+/// Starts the backend in `config` and checks the cursor can be read and
+/// moved with it.
+fn check_backend(config: &Config) -> Outcome {
+    if !config.backend.is_available() {
+        return Outcome::Skip("not available on this platform".into());
+    }
+
+    let mouse = match MouseExt::with_config(config) {
+        Ok(mouse) => mouse,
+        Err(e) => return Outcome::Fail(format!("failed to start ({e})")),
+    };
+    let start = match mouse.pos() {
+        Ok(start) => start,
+        Err(e) => return Outcome::Fail(format!("failed to read the cursor ({e})")),
+    };
+    if !config.backend.reads_position() {
+        return Outcome::Skip("started, but the position can't be read back".into());
+    }
+
+    match mouse.self_test() {
+        Ok(_) => Outcome::Pass(format!("cursor read at {start} and moved")),
+        Err(e) => Outcome::Fail(format!("failed to move the cursor ({e})")),
+    }
+}
@@ -0,0 +1,89 @@
+use crate::bounds::Region;
+use crate::mouse::PointExt;
+
+use std::f64::consts::{PI, TAU};
+
+/// Largest change of heading per second (in radians) while wandering.
+const MAX_TURN_RATE: f64 = 1.5;
+
+/// A point moving continuously at a constant speed in a slowly wandering
+/// direction, bouncing off the edges of a region (or wrapping around to the
+/// opposite edge).
+pub struct Drift {
+    x: f64,
+    y: f64,
+    heading: f64,
+    /// Speed in pixels per second.
+    speed: f64,
+    wrap: bool,
+    /// Whether the last step wrapped around to the opposite edge.
+    wrapped: bool,
+}
+
+impl Drift {
+    pub fn new(start: PointExt, speed: f64, wrap: bool, rng: &fastrand::Rng) -> Self {
+        Self {
+            x: start.x as f64,
+            y: start.y as f64,
+            heading: rng.f64() * TAU,
+            speed,
+            wrap,
+            wrapped: false,
+        }
+    }
+
+    /// Returns `true` if the last step jumped to the opposite edge instead of
+    /// moving continuously.
+    pub fn wrapped(&self) -> bool {
+        self.wrapped
+    }
+
+    /// Continues drifting from `p`, e.g., after the user moved the cursor.
+    pub fn reset(&mut self, p: PointExt) {
+        self.x = p.x as f64;
+        self.y = p.y as f64;
+    }
+
+    /// Reverses the heading, e.g., to bounce off an obstacle.
+    pub fn turn_around(&mut self) {
+        self.heading += PI;
+    }
+
+    /// Advances the drift by `dt` seconds inside `region`, returning the new
+    /// position.
+    pub fn step(&mut self, dt: f64, region: &Region, rng: &fastrand::Rng) -> PointExt {
+        self.heading += (rng.f64() * 2. - 1.) * MAX_TURN_RATE * dt;
+        self.x += self.heading.cos() * self.speed * dt;
+        self.y += self.heading.sin() * self.speed * dt;
+
+        let (x1, x2) = (region.x1 as f64, region.x2 as f64);
+        let (y1, y2) = (region.y1 as f64, region.y2 as f64);
+        if self.wrap {
+            let (x, y) = (wrap(self.x, x1, x2), wrap(self.y, y1, y2));
+            self.wrapped = x != self.x || y != self.y;
+            (self.x, self.y) = (x, y);
+            // rounding can land just past the far edge
+            return region.clamp(PointExt::new(x.round() as i32, y.round() as i32));
+        }
+
+        // reflect the heading off any edge that was crossed
+        if self.x < x1 || self.x > x2 {
+            self.heading = PI - self.heading;
+            self.x = self.x.clamp(x1, x2);
+        }
+        if self.y < y1 || self.y > y2 {
+            self.heading = -self.heading;
+            self.y = self.y.clamp(y1, y2);
+        }
+
+        PointExt::new(self.x.round() as i32, self.y.round() as i32)
+    }
+}
+
+/// Wraps `v` into `[lo, hi]`, where moving past one end continues from the
+/// other.
+fn wrap(v: f64, lo: f64, hi: f64) -> f64 {
+    // the range is inclusive, so a span of one pixel still has a width of 1
+    let width = hi - lo + 1.;
+    lo + (v - lo).rem_euclid(width)
+}
@@ -0,0 +1,66 @@
+use crate::backend::Backend;
+use crate::bounds::Bounds;
+use crate::display::{self, Display};
+
+/// A summary of the environment the application is running in, shown at
+/// startup so bug reports carry enough context to reproduce issues.
+pub struct Environment {
+    displays: Vec<Display>,
+    bounds: Bounds,
+    backend: Backend,
+}
+
+impl Environment {
+    pub fn detect(bounds: &Bounds, backend: Backend) -> Self {
+        Self {
+            // displays are only informational here, so don't fail on them
+            displays: display::all().unwrap_or_default(),
+            bounds: bounds.clone(),
+            backend,
+        }
+    }
+
+    /// Returns each detail as a label and value.
+    pub fn lines(&self) -> Vec<(&'static str, String)> {
+        let displays = match self.displays.len() {
+            0 => "unknown".into(),
+            n => format!(
+                "{n} ({})",
+                self.displays
+                    .iter()
+                    .map(Display::to_string)
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+        };
+
+        vec![
+            (
+                "platform",
+                format!("{} {}", std::env::consts::OS, std::env::consts::ARCH),
+            ),
+            ("display server", display_server().into()),
+            ("backend", self.backend.name().into()),
+            ("displays", displays),
+            ("bounds", self.bounds.to_string()),
+        ]
+    }
+}
+
+/// Returns the name of the windowing system cursor movements go through.
+fn display_server() -> &'static str {
+    match std::env::consts::OS {
+        "windows" => "win32",
+        "macos" => "quartz",
+        _ => {
+            let wayland = std::env::var_os("WAYLAND_DISPLAY").is_some();
+            let x11 = std::env::var_os("DISPLAY").is_some();
+            match (wayland, x11) {
+                (true, true) => "wayland (xwayland)",
+                (true, false) => "wayland",
+                (false, true) => "x11",
+                (false, false) => "none",
+            }
+        }
+    }
+}
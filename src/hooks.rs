@@ -0,0 +1,90 @@
+use crate::config::Config;
+use crate::watchdog;
+
+use anyhow::{anyhow, Result};
+
+use std::process::Stdio;
+use std::time::Instant;
+
+/// Points in the application's lifetime that can run a user command.
+#[derive(Debug, Clone, Copy)]
+pub enum Event {
+    Start,
+    Pause,
+    Resume,
+    Exit,
+}
+
+impl Event {
+    fn name(&self) -> &'static str {
+        match self {
+            Event::Start => "start",
+            Event::Pause => "pause",
+            Event::Resume => "resume",
+            Event::Exit => "exit",
+        }
+    }
+}
+
+/// Shell commands to run on lifecycle events.
+///
+/// Commands receive the event in `MOUSER_EVENT`, why it happened (if known)
+/// in `MOUSER_REASON`, and whole seconds since startup in `MOUSER_ELAPSED`.
+pub struct Hooks {
+    on_start: Option<String>,
+    on_pause: Option<String>,
+    on_resume: Option<String>,
+    on_exit: Option<String>,
+    started: Instant,
+}
+
+impl Hooks {
+    pub fn new(config: &Config) -> Self {
+        Self {
+            on_start: config.on_start.clone(),
+            on_pause: config.on_pause.clone(),
+            on_resume: config.on_resume.clone(),
+            on_exit: config.on_exit.clone(),
+            started: Instant::now(),
+        }
+    }
+
+    /// Runs the command for `event`, if there is one.
+    ///
+    /// Commands run in the background, except on exit where the application
+    /// waits for the command to finish.
+    pub fn fire(&self, event: Event, reason: Option<&str>) -> Result<()> {
+        let command = match event {
+            Event::Start => &self.on_start,
+            Event::Pause => &self.on_pause,
+            Event::Resume => &self.on_resume,
+            Event::Exit => &self.on_exit,
+        };
+        let Some(command) = command else {
+            return Ok(());
+        };
+
+        let mut cmd = watchdog::shell(command);
+        cmd.stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .env("MOUSER_EVENT", event.name())
+            .env("MOUSER_REASON", reason.unwrap_or_default())
+            .env(
+                "MOUSER_ELAPSED",
+                self.started.elapsed().as_secs().to_string(),
+            );
+
+        let error = |e| anyhow!("failed to run {} hook ({e})", event.name());
+        if let Event::Exit = event {
+            cmd.status().map_err(error)?;
+            return Ok(());
+        }
+
+        let mut child = cmd.spawn().map_err(error)?;
+        // reap the command when it finishes so it doesn't linger as a zombie
+        std::thread::spawn(move || child.wait());
+
+        Ok(())
+    }
+}
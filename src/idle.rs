@@ -0,0 +1,14 @@
+use anyhow::{anyhow, Result};
+use user_idle::UserIdle;
+
+use std::time::Duration;
+
+/// Returns how long the system has gone without user input.
+///
+/// Note that injected movements count as input, so this is reset after every
+/// movement the application makes.
+pub fn system_idle_time() -> Result<Duration> {
+    let idle = UserIdle::get_time().map_err(|e| anyhow!("failed to get system idle time ({e})"))?;
+
+    Ok(idle.duration())
+}
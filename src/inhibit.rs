@@ -0,0 +1,99 @@
+#[cfg(not(windows))]
+use anyhow::anyhow;
+use anyhow::{ensure, Result};
+
+#[cfg(not(windows))]
+use std::process::{Child, Command, Stdio};
+#[cfg(not(windows))]
+use std::time::Duration;
+
+/// Keeps the system and display from sleeping until dropped, for power
+/// policies that ignore cursor movement.
+pub struct SleepInhibitor {
+    #[cfg(not(windows))]
+    child: Child,
+}
+
+#[cfg(windows)]
+impl SleepInhibitor {
+    pub fn new() -> Result<Self> {
+        use windows_sys::Win32::System::Power::{
+            SetThreadExecutionState, ES_CONTINUOUS, ES_DISPLAY_REQUIRED, ES_SYSTEM_REQUIRED,
+        };
+
+        // SAFETY: this only changes the power requests of the current thread
+        let previous = unsafe {
+            SetThreadExecutionState(ES_CONTINUOUS | ES_SYSTEM_REQUIRED | ES_DISPLAY_REQUIRED)
+        };
+        ensure!(previous != 0, "failed to set the thread execution state");
+
+        Ok(Self {})
+    }
+}
+
+#[cfg(windows)]
+impl Drop for SleepInhibitor {
+    fn drop(&mut self) {
+        use windows_sys::Win32::System::Power::{SetThreadExecutionState, ES_CONTINUOUS};
+
+        // SAFETY: as above, this only clears the requests made in `new`
+        unsafe {
+            SetThreadExecutionState(ES_CONTINUOUS);
+        }
+    }
+}
+
+#[cfg(not(windows))]
+impl SleepInhibitor {
+    /// Holds an inhibitor through a helper process: `caffeinate` (an IOKit
+    /// power assertion) on macOS, or `systemd-inhibit` (a logind inhibitor
+    /// lock) elsewhere.
+    ///
+    /// The helper also exits when this process does, so nothing is left
+    /// behind if the application is killed before it can clean up.
+    pub fn new() -> Result<Self> {
+        let pid = std::process::id().to_string();
+        let (program, args) = if cfg!(target_os = "macos") {
+            ("caffeinate", vec!["-d", "-i", "-w", pid.as_str()])
+        } else {
+            (
+                "systemd-inhibit",
+                vec![
+                    "--what=idle:sleep",
+                    "--who=mouser",
+                    "--why=Keeping the session active",
+                    "tail",
+                    "--pid",
+                    pid.as_str(),
+                    "-f",
+                    "/dev/null",
+                ],
+            )
+        };
+
+        let mut child = Command::new(program)
+            .args(args)
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+            .map_err(|e| anyhow!("failed to run {program} ({e})"))?;
+        // the helper exits right away if it couldn't take the inhibitor
+        spin_sleep::sleep(Duration::from_millis(200));
+        ensure!(
+            child.try_wait()?.is_none(),
+            "{program} exited without inhibiting sleep"
+        );
+
+        Ok(Self { child })
+    }
+}
+
+#[cfg(not(windows))]
+impl Drop for SleepInhibitor {
+    fn drop(&mut self) {
+        // the inhibitor is released once the helper exits
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
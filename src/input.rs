@@ -1,18 +1,45 @@
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::time::Duration;
 
 use anyhow::Result;
 use crossterm::event::{poll, read, Event, KeyCode, KeyEvent, KeyModifiers};
 
+/// Whether a terminal is available to read key commands from.
+static INTERACTIVE: AtomicBool = AtomicBool::new(true);
+
+/// Enables or disables reading key commands.
+///
+/// When disabled, functions that would wait for input simply sleep for their
+/// timeout instead (e.g., when output is consumed by a status bar).
+pub fn set_interactive(interactive: bool) {
+    INTERACTIVE.store(interactive, Ordering::Relaxed);
+}
+
+pub fn is_interactive() -> bool {
+    INTERACTIVE.load(Ordering::Relaxed)
+}
+
 pub enum KeyCommand {
     Quit,
     ToggleAnimate,
     TogglePause,
+    ToggleHidden,
+    ToggleJitter,
+    CycleBounds,
+    Reanchor,
+    IncreaseInterval,
+    DecreaseInterval,
     None,
     Unknown,
 }
 
 impl KeyCommand {
     pub fn read(timeout: &Duration) -> Result<Self> {
+        if !is_interactive() {
+            spin_sleep::sleep(*timeout);
+            return Ok(Self::None);
+        }
+
         if poll(*timeout)? {
             return Ok(read()?.into());
         }
@@ -32,8 +59,20 @@ impl From<Event> for KeyCommand {
                 'q' => Self::Quit,
                 'p' => Self::TogglePause,
                 'a' => Self::ToggleAnimate,
+                'h' => Self::ToggleHidden,
+                'j' => Self::ToggleJitter,
+                'b' => Self::CycleBounds,
+                'o' => Self::Reanchor,
+                '+' | '=' => Self::IncreaseInterval,
+                '-' => Self::DecreaseInterval,
                 _ => Self::Unknown,
             },
+            // '+' needs shift on most layouts
+            Event::Key(KeyEvent {
+                code: KeyCode::Char('+'),
+                modifiers: KeyModifiers::SHIFT,
+                ..
+            }) => Self::IncreaseInterval,
             Event::Key(KeyEvent {
                 code: KeyCode::Char('c'),
                 modifiers: KeyModifiers::CONTROL,
@@ -44,9 +83,13 @@ impl From<Event> for KeyCommand {
     }
 }
 
-pub fn debounce() -> Result<()> {
+pub fn debounce(timeout: Duration) -> Result<()> {
+    if !is_interactive() {
+        return Ok(());
+    }
+
     loop {
-        if poll(Duration::from_millis(50))? {
+        if poll(timeout)? {
             let _ = read()?;
             continue;
         };
@@ -58,5 +101,10 @@ pub fn debounce() -> Result<()> {
 }
 
 pub fn is_stdin_waiting(timeout: Duration) -> bool {
+    if !is_interactive() {
+        spin_sleep::sleep(timeout);
+        return false;
+    }
+
     crossterm::event::poll(timeout).expect("should be able to poll stdin")
 }
@@ -0,0 +1,252 @@
+use crate::backend::MouseBackend;
+use crate::config::{self, Config};
+use crate::mouse::PointExt;
+use crate::range::DurationRange;
+
+use anyhow::{anyhow, Result};
+use clap::ArgMatches;
+
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+const SAMPLE_INTERVAL: Duration = Duration::from_millis(20);
+const PAUSE_THRESHOLD: Duration = Duration::from_millis(250);
+/// The config profile that learned options are saved to.
+const PROFILE: &str = "learned";
+
+struct Sample {
+    time: Duration,
+    pos: PointExt,
+}
+
+/// A single continuous movement, bounded by pauses on either side.
+struct Stroke {
+    start: PointExt,
+    end: PointExt,
+    length: f64,
+    duration: Duration,
+}
+
+struct Stats {
+    strokes: usize,
+    speed: f64,
+    /// The middle half of the pauses between strokes, if there were any.
+    pauses: Option<DurationRange>,
+    dx: i32,
+    dy: i32,
+}
+
+/// Samples the cursor with the backend from `config`, and saves matching
+/// options as a profile in the config file at `config_path` (or the default
+/// one).
+pub fn run(matches: &ArgMatches, config: &Config, config_path: Option<&Path>) -> Result<()> {
+    let duration = *matches
+        .get_one::<Duration>("DURATION")
+        .expect("duration should be required by clap");
+
+    println!(
+        "Sampling cursor for {:.2}s, use your mouse as you normally would...",
+        duration.as_secs_f32()
+    );
+    let samples = sample(config.backend.create()?.as_ref(), duration)?;
+    let stats = Stats::from_samples(&samples)
+        .ok_or_else(|| anyhow!("not enough mouse movement was observed"))?;
+
+    println!();
+    println!("strokes observed:   {}", stats.strokes);
+    println!("median speed:       {:.0} px/s", stats.speed);
+    match stats.pauses {
+        Some(pauses) => println!("typical pause:      {pauses}"),
+        None => println!("typical pause:      none observed"),
+    }
+    println!("typical jump size:  {}, {}", stats.dx, stats.dy);
+    println!();
+
+    // without any pauses there's nothing to base the interval on, so it's
+    // left to the default
+    let interval = stats.pauses.map(|p| format!("{p} ")).unwrap_or_default();
+    let speed = stats.speed.round().max(1.);
+    println!(
+        "suggested options: {interval}--speed {speed} --relative-bounds {},{}",
+        stats.dx, stats.dy
+    );
+
+    let mut profile = stats
+        .pauses
+        .map(|p| format!("interval = \"{p}\"\n"))
+        .unwrap_or_default();
+    profile.push_str(&format!(
+        "speed = {speed}\nbounds = {{ relative = [{}, {}] }}\n",
+        stats.dx, stats.dy
+    ));
+    let path = config::save_profile(config_path, PROFILE, &profile)?;
+    println!(
+        "saved as [profile.{PROFILE}] in {}, use it with '--profile {PROFILE}'",
+        path.display()
+    );
+
+    Ok(())
+}
+
+fn sample(backend: &dyn MouseBackend, duration: Duration) -> Result<Vec<Sample>> {
+    let start = Instant::now();
+    let mut samples = vec![];
+
+    while start.elapsed() < duration {
+        let pos = backend
+            .pos()
+            .map_err(|e| anyhow!("failed to get mouse position ({e})"))?;
+        samples.push(Sample {
+            time: start.elapsed(),
+            pos,
+        });
+        spin_sleep::sleep(SAMPLE_INTERVAL);
+    }
+
+    Ok(samples)
+}
+
+impl Stats {
+    fn from_samples(samples: &[Sample]) -> Option<Self> {
+        let mut strokes = vec![];
+        let mut pauses = vec![];
+        let mut current: Option<Stroke> = None;
+        let mut last_move = Duration::ZERO;
+
+        for pair in samples.windows(2) {
+            let (prev, next) = (&pair[0], &pair[1]);
+            if prev.pos == next.pos {
+                // a long enough stretch without movement ends the stroke
+                if next.time - last_move >= PAUSE_THRESHOLD {
+                    strokes.extend(current.take());
+                }
+                continue;
+            }
+
+            let stroke = current.get_or_insert_with(|| {
+                if !strokes.is_empty() {
+                    pauses.push(prev.time - last_move);
+                }
+                Stroke {
+                    start: prev.pos,
+                    end: prev.pos,
+                    length: 0.,
+                    duration: Duration::ZERO,
+                }
+            });
+            stroke.end = next.pos;
+            stroke.length += prev.pos.distance(next.pos);
+            stroke.duration += next.time - prev.time;
+            last_move = next.time;
+        }
+        strokes.extend(current);
+
+        if strokes.is_empty() {
+            return None;
+        }
+
+        let speed = median(
+            strokes
+                .iter()
+                .map(|s| s.length / s.duration.as_secs_f64())
+                .collect(),
+        );
+        let pauses = (!pauses.is_empty()).then(|| {
+            let secs = pauses.iter().map(Duration::as_secs_f64).collect::<Vec<_>>();
+            DurationRange::new(
+                Duration::from_secs_f64(percentile(secs.clone(), 0.25)),
+                Duration::from_secs_f64(percentile(secs, 0.75)),
+            )
+        });
+        let dx = percentile(
+            strokes
+                .iter()
+                .map(|s| (s.end.x - s.start.x).abs() as f64)
+                .collect(),
+            0.9,
+        );
+        let dy = percentile(
+            strokes
+                .iter()
+                .map(|s| (s.end.y - s.start.y).abs() as f64)
+                .collect(),
+            0.9,
+        );
+
+        Some(Self {
+            strokes: strokes.len(),
+            speed,
+            pauses,
+            dx: (dx.round() as i32).max(1),
+            dy: (dy.round() as i32).max(1),
+        })
+    }
+}
+
+fn median(values: Vec<f64>) -> f64 {
+    percentile(values, 0.5)
+}
+
+fn percentile(mut values: Vec<f64>, q: f64) -> f64 {
+    if values.is_empty() {
+        return 0.;
+    }
+
+    values.sort_by(|a, b| a.total_cmp(b));
+    let idx = ((values.len() - 1) as f64 * q.clamp(0., 1.)).round() as usize;
+    values[idx]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Returns samples of 100ms strokes separated by `pauses` (in ms).
+    fn samples(pauses: &[u64]) -> Vec<Sample> {
+        let mut samples = vec![];
+        let (mut time, mut x) = (0, 0);
+        let mut push = |time: u64, x: i32| {
+            samples.push(Sample {
+                time: Duration::from_millis(time),
+                pos: PointExt::new(x, 0),
+            })
+        };
+
+        push(time, x);
+        for pause in std::iter::once(0).chain(pauses.iter().copied()) {
+            for _ in 0..pause / 20 {
+                time += 20;
+                push(time, x);
+            }
+            for _ in 0..5 {
+                time += 20;
+                x += 1;
+                push(time, x);
+            }
+        }
+
+        samples
+    }
+
+    #[test]
+    fn interval_is_the_middle_of_the_pauses() {
+        let stats = Stats::from_samples(&samples(&[3000, 1000, 5000, 2000, 4000])).unwrap();
+
+        assert_eq!(stats.strokes, 6);
+        assert_eq!(
+            stats.pauses,
+            Some(DurationRange::new(
+                Duration::from_secs(2),
+                Duration::from_secs(4)
+            ))
+        );
+    }
+
+    #[test]
+    fn no_interval_without_pauses() {
+        let stats = Stats::from_samples(&samples(&[])).unwrap();
+
+        assert_eq!(stats.strokes, 1);
+        assert_eq!(stats.pauses, None);
+    }
+}
@@ -1,211 +1,1265 @@
 mod animation;
+mod backend;
 mod bounds;
+mod capture;
 mod cli;
 mod config;
+mod desktop;
+mod display;
+mod doctor;
+mod drift;
+mod environment;
+mod hooks;
+mod idle;
+mod inhibit;
 mod input;
+mod learn;
 mod mouse;
+mod path;
+mod pattern;
+mod range;
+mod schedule;
+mod session;
+mod source;
+mod state;
+mod target;
+mod trace;
+mod ui;
+mod watchdog;
+mod window;
 
-use bounds::Bounds;
+use bounds::{Bounds, Region};
 use config::Config;
+use display::{Display, Units};
+use drift::Drift;
+use environment::Environment;
+use hooks::{Event, Hooks};
+use inhibit::SleepInhibitor;
 use input::KeyCommand;
 use mouse::{MouseExt, PointExt};
+use schedule::Schedule;
+use session::{LockMonitor, OnLock};
+use source::PointSource;
+use state::State;
+use target::Target;
+use ui::{Status, StatusBar, Ui};
+use watchdog::Watchdog;
 
 use anyhow::{anyhow, bail, Result};
-use crossterm::cursor::{MoveTo, MoveToColumn, MoveToNextLine};
-use crossterm::style::{Color, Print, ResetColor, SetForegroundColor, Stylize};
-use crossterm::terminal::{
-    disable_raw_mode, enable_raw_mode, Clear, ClearType, EnterAlternateScreen, LeaveAlternateScreen,
-};
-use crossterm::{cursor, execute};
-use std::io::stdout;
+use chrono::{Local, NaiveDate};
 
+use std::fmt;
+use std::path::PathBuf;
 use std::process::ExitCode;
-use std::time::Duration;
+use std::time::{Duration, Instant};
+
+/// Jitter percentage used when toggled on without `--jitter`.
+const DEFAULT_JITTER: f64 = 20.;
+/// Factor the interval is multiplied or divided by with the `+`/`-` keys.
+const INTERVAL_STEP: f64 = 1.25;
+/// Shortest interval the `-` key can reduce to.
+const MIN_INTERVAL: Duration = Duration::from_millis(100);
+/// How often the daily usage is saved while drifting.
+const USAGE_SAVE_INTERVAL: Duration = Duration::from_secs(5);
 
 fn main() -> ExitCode {
     let matches = cli::build().get_matches();
+    // needs to happen before any coordinates are read
+    desktop::set_dpi_aware();
+    if let Some((name, sub_matches)) = matches.subcommand() {
+        let result = match name {
+            "config" => config::run(sub_matches),
+            "learn" => Config::load(&matches).and_then(|c| {
+                learn::run(
+                    sub_matches,
+                    &c,
+                    matches.get_one::<PathBuf>("config").map(PathBuf::as_path),
+                )
+            }),
+            "displays" => display::run(),
+            "doctor" => Config::load(&matches).and_then(doctor::run),
+            // sample at the configured fps unless told otherwise
+            "record" => Config::load(&matches).and_then(|c| trace::run(sub_matches, c.fps)),
+            "replay" => Config::load(&matches).and_then(|c| trace::replay(sub_matches, &c)),
+            _ => unreachable!("subcommands should be defined by clap"),
+        };
+        return match result {
+            Ok(_) => ExitCode::SUCCESS,
+            Err(e) => {
+                eprintln!("error: {e}");
+                ExitCode::FAILURE
+            }
+        };
+    }
 
-    let interval = *matches
-        .get_one::<Duration>("INTERVAL")
-        .expect("interval should be required by clap");
-    let pause_interval = *matches
-        .get_one::<Duration>("pause-interval")
-        .unwrap_or(&interval);
-    let fps = matches
-        .get_one::<u32>("fps")
-        .copied()
-        .expect("fps should be required by clap");
-    let bounds = Bounds::from(&matches);
-    if bounds.has_empty_range() {
-        eprintln!("error: bounds {bounds} will result in no mouse movement");
+    let mut config = match Config::load(&matches) {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("error: {e}");
+            return ExitCode::FAILURE;
+        }
+    };
+    if let Some(target) = matches.get_one::<Target>("target") {
+        let max_interval = target.max_interval();
+        if config::is_explicit(&matches, "INTERVAL") {
+            if config.interval.max > max_interval {
+                eprintln!(
+                    "warning: interval {} is longer than the {target} preset ({:.2}s)",
+                    config.interval,
+                    max_interval.as_secs_f32()
+                );
+            }
+        } else {
+            config.interval = max_interval.into();
+        }
+    }
+    if config.bounds.has_empty_range() {
+        eprintln!(
+            "error: bounds {} will result in no mouse movement",
+            config.bounds
+        );
+        return ExitCode::FAILURE;
+    }
+    for (name, bounds) in &config.bounds_presets {
+        if bounds.has_empty_range() {
+            eprintln!("error: bounds preset '{name}' ({bounds}) will result in no mouse movement");
+            return ExitCode::FAILURE;
+        }
+    }
+    // absolute coordinates are measured in `--units`, from the chosen
+    // monitor's top left corner if there is one
+    let monitor = match config.monitor.as_deref().map(display::find).transpose() {
+        Ok(monitor) => monitor,
+        Err(e) => {
+            eprintln!("error: {e}");
+            return ExitCode::FAILURE;
+        }
+    };
+    if monitor.is_some() || config.units != Units::cursor() {
+        let displays = match &monitor {
+            Some(_) => vec![],
+            None => match display::all() {
+                Ok(displays) => displays,
+                Err(e) => {
+                    eprintln!("error: {e}");
+                    return ExitCode::FAILURE;
+                }
+            },
+        };
+        let units = config.units;
+        let to_cursor = |p: PointExt| match &monitor {
+            Some(monitor) => monitor.at_offset(p.x, p.y, units),
+            None => display::to_cursor(p, units, &displays),
+        };
+        config.bounds = config.bounds.map(to_cursor);
+        for bounds in config.bounds_presets.values_mut() {
+            *bounds = bounds.map(to_cursor);
+        }
+        for zone in &mut config.exclude {
+            *zone = zone.map(to_cursor);
+        }
+    }
+    let has_center = std::iter::once(&config.bounds)
+        .chain(config.bounds_presets.values())
+        .any(|bounds| matches!(bounds, Bounds::Center { .. }));
+    if has_center {
+        let display = match &monitor {
+            Some(monitor) => Ok(monitor.clone()),
+            None => display::primary(),
+        };
+        match display {
+            Ok(display) => {
+                config.bounds = config.bounds.center_on(&display, config.units);
+                for bounds in config.bounds_presets.values_mut() {
+                    *bounds = bounds.center_on(&display, config.units);
+                }
+            }
+            Err(e) => {
+                eprintln!("error: {e}");
+                return ExitCode::FAILURE;
+            }
+        }
+    }
+    if matches.get_flag("capture-bounds") {
+        match capture::corners() {
+            Ok(region) => {
+                config.bounds = Bounds::from(region);
+                if matches.get_flag("print-captured") {
+                    println!("{}", captured_flags(region, &config, monitor.as_ref()));
+                }
+            }
+            Err(e) => {
+                eprintln!("error: {e}");
+                return ExitCode::FAILURE;
+            }
+        }
+    }
+    let has_absolute = std::iter::once(&config.bounds)
+        .chain(config.bounds_presets.values())
+        .any(|bounds| !bounds.is_relative());
+    if has_absolute {
+        let screen = match &monitor {
+            Some(monitor) => Ok(monitor.region()),
+            None => display::desktop(),
+        };
+        match screen {
+            Ok(desktop) => {
+                let result = fit_bounds("bounds", &mut config.bounds, &desktop).and_then(|_| {
+                    config
+                        .bounds_presets
+                        .iter_mut()
+                        .try_for_each(|(name, bounds)| {
+                            fit_bounds(&format!("bounds preset '{name}'"), bounds, &desktop)
+                        })
+                });
+                if let Err(e) = result {
+                    eprintln!("error: {e}");
+                    return ExitCode::FAILURE;
+                }
+            }
+            Err(e) => eprintln!("warning: unable to check bounds against the screen ({e})"),
+        }
+    }
+    let excluded = std::iter::once(("bounds".to_string(), &config.bounds))
+        .chain(
+            config
+                .bounds_presets
+                .iter()
+                .map(|(name, bounds)| (format!("bounds preset '{name}'"), bounds)),
+        )
+        .find(|(_, bounds)| is_fully_excluded(bounds, &config.exclude));
+    if let Some((label, bounds)) = excluded {
+        eprintln!("error: {label} {bounds} are entirely inside excluded zones");
         return ExitCode::FAILURE;
     }
-    let animate = !matches.get_flag("no-animate");
-    let auto_pause = !matches.get_flag("no-autopause");
+    let statusbar = matches.get_one::<StatusBar>("statusbar").copied();
+
+    let mut mouse = match MouseExt::with_config(&config) {
+        Ok(mouse) => mouse,
+        Err(e) => {
+            eprintln!(
+                "error: failed to start the {} backend ({e})",
+                config.backend.name()
+            );
+            return ExitCode::FAILURE;
+        }
+    };
+    if matches.get_flag("simulate-user") {
+        let rng = config
+            .seed
+            .map_or_else(fastrand::Rng::new, fastrand::Rng::with_seed);
+        mouse.simulate_user(rng);
+    }
+    // a tracked position always looks moved, so the check would prove nothing
+    if !matches.get_flag("no-check") && config.backend.reads_position() {
+        if let Err(e) = mouse.self_test() {
+            eprintln!("error: startup cursor check failed ({e})");
+            eprintln!("hint: {}", self_test_hint());
+            return ExitCode::FAILURE;
+        }
+    }
+    if matches.get_flag("once") {
+        return match once(&mut mouse, &config) {
+            Ok(_) => ExitCode::SUCCESS,
+            Err(e) => {
+                eprintln!("error: {e}");
+                ExitCode::FAILURE
+            }
+        };
+    }
+
+    // released when dropped at the end of `main`
+    let _inhibitor = if config.inhibit_sleep {
+        match SleepInhibitor::new() {
+            Ok(inhibitor) => Some(inhibitor),
+            Err(e) => {
+                eprintln!("error: failed to inhibit sleep ({e})");
+                return ExitCode::FAILURE;
+            }
+        }
+    } else {
+        None
+    };
+    let mut state = match State::load() {
+        Ok(state) => state,
+        Err(e) => {
+            eprintln!("error: {e}");
+            return ExitCode::FAILURE;
+        }
+    };
+    let mut ui = Ui::new(
+        statusbar,
+        Environment::detect(&config.bounds, config.backend),
+    );
+    let hooks = Hooks::new(&config);
+    let mut summary = Summary::new();
 
-    let mut config = Config {
-        interval,
-        pause_interval,
-        fps,
-        bounds,
-        animate,
-        auto_pause,
+    let result = ui
+        .start()
+        .and_then(|_| hooks.fire(Event::Start, None))
+        .and_then(|_| {
+            run(
+                &mut mouse,
+                &mut config,
+                &mut ui,
+                &mut state,
+                &hooks,
+                &mut summary,
+            )
+        });
+    let reason = match &result {
+        Ok(exit) => exit.reason(),
+        Err(_) => "error",
     };
 
-    let mut mouse = MouseExt::with_config(&config);
+    ui.finish();
 
-    let mut stdout = stdout();
-    execute!(
-        stdout,
-        cursor::Hide,
-        EnterAlternateScreen,
-        Clear(ClearType::All),
-    )
-    .expect("should be able to execute crossterm commands");
-    enable_raw_mode().expect("should be able to start raw mode");
+    if result.is_ok() && config.count.is_some() {
+        // stdout belongs to the status bar, if there is one
+        match statusbar {
+            Some(_) => eprintln!("{summary}"),
+            None => println!("{summary}"),
+        }
+    }
 
-    let code = match run(&mut mouse, &mut config) {
+    let mut code = match result {
         Ok(_) => ExitCode::SUCCESS,
         Err(e) => {
             eprintln!("error: {e}");
             ExitCode::FAILURE
         }
     };
-
-    disable_raw_mode().expect("should be able to disable raw mode");
-    execute!(stdout, cursor::Show, LeaveAlternateScreen)
-        .expect("should be able to leave alternate screen");
+    if let Err(e) = hooks.fire(Event::Exit, Some(reason)) {
+        eprintln!("error: {e}");
+        code = ExitCode::FAILURE;
+    }
 
     code
 }
 
-fn run(mouse: &mut MouseExt, config: &mut Config) -> Result<()> {
-    let mut stdout = stdout();
+/// Why the application stopped moving the cursor.
+#[derive(Debug, Clone, Copy)]
+enum Exit {
+    /// The user asked to quit.
+    Quit,
+    /// The `--duration` or `--until` deadline passed.
+    Deadline,
+    /// The `--count` movements were made.
+    Count,
+    /// The session was locked with `--on-lock quit`.
+    Locked,
+}
 
-    print_header(&mut stdout);
+impl Exit {
+    /// Returns the reason given to the exit hook.
+    fn reason(self) -> &'static str {
+        match self {
+            Exit::Quit => "quit",
+            Exit::Deadline => "deadline",
+            Exit::Count => "count",
+            Exit::Locked => "locked",
+        }
+    }
+}
 
-    let rng = fastrand::Rng::new();
-    let mut orig = mouse
-        .pos()
-        .map_err(|_| anyhow!("failed to get mouse position"))?;
+/// Totals reported when the number of movements is limited.
+struct Summary {
+    moves: u64,
+    distance: f64,
+    started: Instant,
+}
+
+impl Summary {
+    fn new() -> Self {
+        Self {
+            moves: 0,
+            distance: 0.,
+            started: Instant::now(),
+        }
+    }
+}
 
-    let poll_time = Duration::from_millis(25);
+impl fmt::Display for Summary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "made {} movements covering {:.0}px in {:.2}s",
+            self.moves,
+            self.distance,
+            self.started.elapsed().as_secs_f32()
+        )
+    }
+}
 
-    let action_text = if config.animate {
-        " animating to "
-    } else {
-        " placed cursor at "
-    };
+/// Returns the most likely reason the cursor can't be controlled on this
+/// platform.
+fn self_test_hint() -> &'static str {
+    match std::env::consts::OS {
+        "macos" => {
+            "your terminal may need Accessibility permission \
+            (System Settings > Privacy & Security > Accessibility)"
+        }
+        "linux" => {
+            "cursor positioning requires an X11 session, on Wayland or a \
+            console use '--backend uinput' (needs write access to /dev/uinput)"
+        }
+        "windows" => {
+            "input can't be injected into elevated windows or some remote \
+            desktop sessions, try running from the interactive session"
+        }
+        _ => "this platform may not allow applications to move the cursor",
+    }
+}
 
-    execute!(
-        stdout,
-        Clear(ClearType::CurrentLine),
-        Print("Status:".bold().dim()),
-        MoveToColumn(0),
-    )?;
+fn run(
+    mouse: &mut MouseExt,
+    config: &mut Config,
+    ui: &mut Ui,
+    state: &mut State,
+    hooks: &Hooks,
+    summary: &mut Summary,
+) -> Result<Exit> {
+    if let Some(delay) = config.start_after {
+        if let Some(exit) = warm_up(config, ui, delay)? {
+            return Ok(exit);
+        }
+    }
 
+    let rng = config
+        .seed
+        .map_or_else(fastrand::Rng::new, fastrand::Rng::with_seed);
+    let mut orig = mouse
+        .pos()
+        .map_err(|_| anyhow!("failed to get mouse position"))?;
+
+    let mut screen = origin_screen(config, orig)?;
+    let safe = safe_area(config)?;
     let mut last_p = orig;
+    let mut source = source::from_config(config)?;
+    let mut interrupted = None;
+    let mut jitter = config.jitter;
+    // the starting bounds come first, so cycling always returns to them
+    let presets = std::iter::once((String::from("default"), config.bounds.clone()))
+        .chain(config.bounds_presets.clone())
+        .collect::<Vec<_>>();
+    let mut preset = 0;
+    if presets.len() > 1 {
+        ui.set_bounds_name(&presets[preset].0);
+    }
+    let start = Instant::now();
+    let until = config.until.map(|time| start + schedule::until(time));
+    let mut gates = Gates::new(config, state, until.filter(|_| config.until_pause));
+    let deadline = config
+        .duration
+        .map(|duration| start + duration)
+        .into_iter()
+        .chain(until.filter(|_| !config.until_pause))
+        .min();
+    if let Some(deadline) = deadline {
+        ui.set_deadline(deadline);
+    }
+    if let Some(speed) = config.drift {
+        return drift(mouse, config, ui, state, hooks, &mut gates, summary, speed);
+    }
     loop {
-        match KeyCommand::read(&poll_time)? {
-            KeyCommand::Quit => return Ok(()),
+        if ui.is_past_deadline() {
+            return Ok(Exit::Deadline);
+        }
+        if config.count.is_some_and(|count| summary.moves >= count) {
+            return Ok(Exit::Count);
+        }
+
+        match KeyCommand::read(&config.input.poll)? {
+            KeyCommand::Quit => return Ok(Exit::Quit),
             KeyCommand::ToggleAnimate => {
-                input::debounce()?;
+                input::debounce(config.input.debounce)?;
                 config.animate = !config.animate;
                 mouse.toggle_animate();
             }
             KeyCommand::TogglePause => {
-                execute!(
-                    stdout,
-                    Clear(ClearType::CurrentLine),
-                    Print("Status:".bold().dim()),
-                    SetForegroundColor(Color::Yellow),
-                    Print(" paused"),
-                    ResetColor,
-                    Print(" (press ".dim()),
-                    Print("p".bold()),
-                    Print(" to unpause)".dim()),
-                    MoveToColumn(0),
-                )?;
-                input::debounce()?;
-                'pause: loop {
-                    match KeyCommand::read(&Duration::from_secs(60))? {
-                        KeyCommand::Quit => return Ok(()),
-                        KeyCommand::TogglePause => {
-                            input::debounce()?;
-                            break 'pause;
-                        }
-                        _ => (),
-                    }
+                input::debounce(config.input.debounce)?;
+                gates.pause("key");
+            }
+            KeyCommand::ToggleJitter => {
+                input::debounce(config.input.debounce)?;
+                jitter = match jitter {
+                    Some(_) => None,
+                    None => Some(config.jitter.unwrap_or(DEFAULT_JITTER)),
+                };
+            }
+            KeyCommand::IncreaseInterval => {
+                input::debounce(config.input.debounce)?;
+                config.interval = config.interval.scale(INTERVAL_STEP);
+            }
+            KeyCommand::DecreaseInterval => {
+                input::debounce(config.input.debounce)?;
+                let interval = config.interval.scale(1. / INTERVAL_STEP);
+                if interval.min >= MIN_INTERVAL {
+                    config.interval = interval;
                 }
             }
+            KeyCommand::CycleBounds if presets.len() > 1 => {
+                input::debounce(config.input.debounce)?;
+                preset = (preset + 1) % presets.len();
+                let (name, bounds) = &presets[preset];
+                config.bounds = bounds.clone();
+                screen = origin_screen(config, orig)?;
+                ui.set_bounds_name(name);
+            }
+            KeyCommand::Reanchor => {
+                input::debounce(config.input.debounce)?;
+                orig = mouse
+                    .pos()
+                    .map_err(|_| anyhow!("failed to get mouse position"))?;
+                screen = origin_screen(config, orig)?;
+            }
+            KeyCommand::ToggleHidden => {
+                input::debounce(config.input.debounce)?;
+                ui.toggle_hidden()?;
+            }
             _ => (),
         };
 
-        let p = sample_point(&rng, &config.bounds, orig, last_p);
-        execute!(
-            stdout,
-            Clear(ClearType::CurrentLine),
-            Print("Status:".bold().dim()),
-            Print(action_text.dim()),
-            SetForegroundColor(Color::Cyan),
-            Print(p),
-            ResetColor,
-            MoveToColumn(0),
-        )?;
-
-        match mouse.move_to(p) {
-            Ok(_) => (),
+        if let Some(exit) = gates.wait(config, ui, state, hooks)? {
+            return Ok(exit);
+        }
+        let active_start = Instant::now();
+
+        let pos = mouse
+            .pos()
+            .map_err(|_| anyhow!("failed to get mouse position"))?;
+        // returning movements come back to wherever the user left the cursor
+        let anchor = config.return_to_anchor.then_some(pos);
+
+        // micro-jiggles return to wherever the cursor already is
+        let mut p = if config.micro {
+            pos
+        } else if let Some(p) = interrupted.take() {
+            p
+        } else {
+            let bounds = match wait_for_bounds(config, ui, &rng)? {
+                Ok(bounds) => bounds,
+                Err(exit) => return Ok(exit),
+            };
+            let mut region = movement_region(&bounds, orig, screen.as_ref(), safe.as_ref());
+            if let Some(axis) = config.lock_axis {
+                region = axis.lock(&region, pos);
+            }
+            // come back in at the nearest point instead of crossing the screen
+            let clamped = region.clamp(pos);
+            if config.clamp_back && clamped != pos && !source::is_excluded(&config.exclude, clamped)
+            {
+                clamped
+            } else {
+                let p = source.next(&region, last_p, &rng);
+                match config.grid {
+                    Some(grid) => snap(&region, p, grid, &config.exclude),
+                    None => p,
+                }
+            }
+        };
+        if let Some(max) = config.max_distance {
+            p = within(pos, p, max, &config.exclude);
+        }
+        let mut interval = config.interval.sample(&rng);
+        if let Some(percent) = jitter {
+            interval = range::jitter(interval, percent, &rng);
+        }
+        ui.show(Status::Moving {
+            to: p,
+            animate: config.animate,
+            interval,
+        })?;
+
+        let moved = if config.micro {
+            mouse.micro(interval)
+        } else if let Some(anchor) = anchor {
+            // split the interval between the way out and the way back
+            let leg = interval / 2;
+            mouse
+                .move_to(p, leg)
+                .and_then(|_| mouse.move_to(anchor, leg))
+        } else {
+            mouse.move_to(p, interval)
+        };
+        match moved {
+            Ok(_) => {
+                summary.moves += 1;
+                summary.distance += pos.distance(p);
+                if let Some(anchor) = anchor {
+                    summary.distance += p.distance(anchor);
+                }
+                if let Some(dwell) = config.dwell {
+                    let duration = dwell.sample(&rng);
+                    ui.show(Status::Dwelling {
+                        at: anchor.unwrap_or(p),
+                        duration,
+                    })?;
+                    // the user taking over just ends the dwell, and the next
+                    // movement notices them
+                    match mouse.dwell(duration) {
+                        Ok(_) | Err(mouse::MouseError::Busy) => (),
+                        Err(e) => bail!("failed to move mouse ({e})"),
+                    }
+                }
+            }
             Err(err) => match err {
                 mouse::MouseError::Busy => {
-                    auto_pause(config, mouse)?;
+                    hooks.fire(Event::Pause, Some("auto"))?;
+                    auto_pause(config, mouse, ui)?;
+                    hooks.fire(Event::Resume, Some("auto"))?;
+                    if config.resume {
+                        interrupted = Some(p);
+                    }
                     if config.bounds.is_relative() {
                         orig = mouse
                             .pos()
                             .map_err(|_| anyhow!("failed to get mouse position"))?;
+                        screen = origin_screen(config, orig)?;
+                    }
+                }
+                mouse::MouseError::SecureDesktop => {
+                    let exit = wait_while(config, ui, Duration::from_secs(1), || {
+                        Ok(desktop::is_secure_desktop_active().then_some(Status::SecureDesktop))
+                    })?;
+                    if let Some(exit) = exit {
+                        return Ok(exit);
                     }
                 }
                 e => bail!("failed to move mouse ({e})"),
             },
         }
 
-        last_p = p;
+        if config.daily_limit.is_some() {
+            state.usage.add(today(), active_start.elapsed());
+            state.save()?;
+        }
+
+        last_p = anchor.unwrap_or(p);
     }
 }
 
-fn sample_point(
-    rng: &fastrand::Rng,
-    bounds: &Bounds,
-    orig: PointExt,
-    last_p: PointExt,
-) -> PointExt {
+/// Moves the cursor continuously at `speed` pixels per second, wandering
+/// around the bounds until the deadline or the user quits.
+///
+/// Drifting is held back by the same pauses, schedules, and limits as
+/// hopping, which are checked every frame.
+#[allow(clippy::too_many_arguments)]
+fn drift(
+    mouse: &MouseExt,
+    config: &Config,
+    ui: &mut Ui,
+    state: &mut State,
+    hooks: &Hooks,
+    gates: &mut Gates,
+    summary: &mut Summary,
+    speed: f64,
+) -> Result<Exit> {
+    let rng = config
+        .seed
+        .map_or_else(fastrand::Rng::new, fastrand::Rng::with_seed);
+    let mut orig = mouse
+        .pos()
+        .map_err(|_| anyhow!("failed to get mouse position"))?;
+    let mut screen = origin_screen(config, orig)?;
+    let safe = safe_area(config)?;
+    // drifting between separate regions would cross the gaps, so stay in one
+    // (and in the same window) until the user takes over
+    let mut bounds = match wait_for_bounds(config, ui, &rng)? {
+        Ok(bounds) => bounds,
+        Err(exit) => return Ok(exit),
+    };
+    let mut drift = Drift::new(orig, speed, config.wrap, &rng);
+    let mut last_p = orig;
+    let frame_time = Duration::from_secs_f64(1. / config.fps as f64);
+    let mut last_frame = Instant::now();
+    let mut last_save = Instant::now();
+
+    ui.show(Status::Drifting { speed })?;
     loop {
-        let result = match *bounds {
-            Bounds::Rect { x1, y1, x2, y2 } => {
-                let x_range = if x1 <= x2 { x1..=x2 } else { x2..=x1 };
-                let y_range = if y1 <= y2 { y1..=y2 } else { y2..=y1 };
-                PointExt {
-                    x: rng.i32(x_range),
-                    y: rng.i32(y_range),
+        if ui.is_past_deadline() {
+            return Ok(Exit::Deadline);
+        }
+
+        // reading keys also waits out the rest of the frame
+        match KeyCommand::read(&frame_time)? {
+            KeyCommand::Quit => return Ok(Exit::Quit),
+            KeyCommand::TogglePause => {
+                input::debounce(config.input.debounce)?;
+                gates.pause("key");
+            }
+            KeyCommand::Reanchor => {
+                input::debounce(config.input.debounce)?;
+                orig = last_p;
+                screen = origin_screen(config, orig)?;
+            }
+            KeyCommand::ToggleHidden => {
+                input::debounce(config.input.debounce)?;
+                ui.toggle_hidden()?;
+                ui.show(Status::Drifting { speed })?;
+            }
+            _ => (),
+        }
+
+        if !gates.is_open(config, state)? {
+            if let Some(exit) = gates.wait(config, ui, state, hooks)? {
+                return Ok(exit);
+            }
+            // the user may have moved the cursor while waiting
+            last_p = mouse
+                .pos()
+                .map_err(|_| anyhow!("failed to get mouse position"))?;
+            drift.reset(last_p);
+            last_frame = Instant::now();
+            ui.show(Status::Drifting { speed })?;
+        }
+
+        let region = movement_region(&bounds, orig, screen.as_ref(), safe.as_ref());
+        let elapsed = last_frame.elapsed();
+        let dt = elapsed.as_secs_f64();
+        last_frame = Instant::now();
+        if config.daily_limit.is_some() {
+            state.usage.add(today(), elapsed);
+            // saving every frame would mean constant disk writes
+            if last_save.elapsed() >= USAGE_SAVE_INTERVAL {
+                state.save()?;
+                last_save = Instant::now();
+            }
+        }
+        let p = drift.step(dt, &region, &rng);
+        if p == last_p {
+            continue;
+        }
+        if source::is_excluded(&config.exclude, p) {
+            // bounce off excluded zones like the edges of the bounds
+            drift.reset(last_p);
+            drift.turn_around();
+            continue;
+        }
+
+        match mouse.step_to(last_p, p) {
+            Ok(_) => {
+                // jumping to the opposite edge isn't travelled
+                if !drift.wrapped() {
+                    summary.distance += last_p.distance(p);
                 }
+                last_p = p;
             }
-            Bounds::Relative { dx: x, dy: y } => PointExt {
-                x: rng.i32((orig.x - x)..=(orig.x + x)),
-                y: rng.i32((orig.y - y)..=(orig.y + y)),
-            },
+            Err(mouse::MouseError::Busy) => {
+                hooks.fire(Event::Pause, Some("auto"))?;
+                auto_pause(config, mouse, ui)?;
+                hooks.fire(Event::Resume, Some("auto"))?;
+                last_p = mouse
+                    .pos()
+                    .map_err(|_| anyhow!("failed to get mouse position"))?;
+                if config.bounds.is_relative() {
+                    orig = last_p;
+                    screen = origin_screen(config, orig)?;
+                }
+                bounds = match wait_for_bounds(config, ui, &rng)? {
+                    Ok(bounds) => bounds,
+                    Err(exit) => return Ok(exit),
+                };
+                drift.reset(last_p);
+                last_frame = Instant::now();
+                ui.show(Status::Drifting { speed })?;
+            }
+            Err(e) => bail!("failed to move mouse ({e})"),
+        }
+    }
+}
+
+/// Everything that holds movements back until it clears: pausing, the
+/// watchdog, a locked session, the idle threshold, the schedule, and the
+/// daily limit.
+struct Gates {
+    paused: Option<&'static str>,
+    pause_at: Option<Instant>,
+    watchdog: Option<Watchdog>,
+    lock: Option<LockMonitor>,
+}
+
+impl Gates {
+    /// Starts paused if asked to (or if paused when last run), and pauses
+    /// again at `pause_at` if given.
+    fn new(config: &Config, state: &State, pause_at: Option<Instant>) -> Self {
+        // status bars can't send key commands, so they would never unpause
+        let paused = match (config.paused, state.paused) {
+            _ if !input::is_interactive() => None,
+            (true, _) => Some("startup"),
+            (false, true) => Some("restored"),
+            (false, false) => None,
+        };
+
+        Self {
+            paused,
+            pause_at,
+            watchdog: config
+                .watchdog
+                .clone()
+                .map(|command| Watchdog::new(command, config.watchdog_interval)),
+            lock: (config.on_lock != OnLock::Continue).then(LockMonitor::new),
+        }
+    }
+
+    /// Pauses movements at the next check, giving `reason` to the hooks.
+    fn pause(&mut self, reason: &'static str) {
+        self.paused = Some(reason);
+    }
+
+    /// Returns `true` if nothing is holding movements back right now,
+    /// without waiting.
+    fn is_open(&mut self, config: &Config, state: &State) -> Result<bool> {
+        let until_reached = self.pause_at.is_some_and(|at| Instant::now() >= at);
+        let watchdog_ok = match self.watchdog.as_mut() {
+            Some(watchdog) => watchdog.is_ok()?,
+            None => true,
+        };
+        let locked = match self.lock.as_mut() {
+            Some(lock) => lock.is_locked()?,
+            None => false,
         };
+        let idle = match config.idle_target() {
+            Some(target) => idle::system_idle_time()? >= target,
+            None => true,
+        };
+        let scheduled = config
+            .schedule()
+            .is_none_or(|schedule| schedule.is_active(Local::now().naive_local()));
+        let limited = config
+            .daily_limit
+            .is_some_and(|limit| state.usage.active(today()) >= limit);
+
+        Ok(self.paused.is_none()
+            && !until_reached
+            && watchdog_ok
+            && !locked
+            && idle
+            && scheduled
+            && !limited)
+    }
+
+    /// Waits until nothing holds movements back, firing the hooks and
+    /// saving the paused state along the way. Returns why to stop if waiting
+    /// was cut short.
+    fn wait(
+        &mut self,
+        config: &Config,
+        ui: &mut Ui,
+        state: &mut State,
+        hooks: &Hooks,
+    ) -> Result<Option<Exit>> {
+        if self.pause_at.is_some_and(|at| Instant::now() >= at) {
+            self.pause_at = None;
+            self.paused = Some("until");
+        }
+
+        if let Some(reason) = self.paused.take() {
+            state.paused = true;
+            state.save()?;
+            hooks.fire(Event::Pause, Some(reason))?;
+            if let Some(exit) = pause(config, ui)? {
+                return Ok(Some(exit));
+            }
+            state.paused = false;
+            state.save()?;
+            hooks.fire(Event::Resume, Some("key"))?;
+        }
+
+        if let Some(watchdog) = self.watchdog.as_mut() {
+            if let Some(exit) = wait_for_watchdog(config, ui, watchdog)? {
+                return Ok(Some(exit));
+            }
+        }
+
+        if let Some(lock) = self.lock.as_mut() {
+            if let Some(exit) = wait_for_unlock(config, ui, hooks, lock)? {
+                return Ok(Some(exit));
+            }
+        }
+
+        if let Some(target) = config.idle_target() {
+            if let Some(exit) = wait_for_idle(config, ui, target)? {
+                return Ok(Some(exit));
+            }
+        }
+
+        if let Some(schedule) = config.schedule() {
+            if let Some(exit) = wait_for_schedule(config, ui, &schedule)? {
+                return Ok(Some(exit));
+            }
+        }
+
+        if let Some(limit) = config.daily_limit {
+            if state.usage.active(today()) >= limit {
+                if let Some(exit) = wait_for_tomorrow(config, ui, limit)? {
+                    return Ok(Some(exit));
+                }
+            }
+        }
+
+        Ok(None)
+    }
+}
+
+/// Makes a single movement without the TUI, for use from schedulers.
+fn once(mouse: &mut MouseExt, config: &Config) -> Result<()> {
+    // there may not be a terminal to read key commands from
+    input::set_interactive(false);
+
+    let rng = config
+        .seed
+        .map_or_else(fastrand::Rng::new, fastrand::Rng::with_seed);
+    let orig = mouse
+        .pos()
+        .map_err(|_| anyhow!("failed to get mouse position"))?;
+    let screen = origin_screen(config, orig)?;
+    let mut region = movement_region(
+        &config.bounds.choose(&rng)?,
+        orig,
+        screen.as_ref(),
+        safe_area(config)?.as_ref(),
+    );
+    if let Some(axis) = config.lock_axis {
+        region = axis.lock(&region, orig);
+    }
+    let mut p = source::from_config(config)?.next(&region, orig, &rng);
+    if let Some(grid) = config.grid {
+        p = snap(&region, p, grid, &config.exclude);
+    }
+    if let Some(max) = config.max_distance {
+        p = within(orig, p, max, &config.exclude);
+    }
+
+    match mouse.move_to(p, config.interval.sample(&rng)) {
+        // the user moving the mouse is just as good as our movement
+        Ok(_) | Err(mouse::MouseError::Busy) => Ok(()),
+        Err(e) => bail!("failed to move mouse ({e})"),
+    }
+}
+
+/// Returns the display relative movements should be kept on, if any.
+fn origin_screen(config: &Config, orig: PointExt) -> Result<Option<Display>> {
+    if let Some(monitor) = &config.monitor {
+        return display::find(monitor).map(Some);
+    }
+    if !config.stay_on_screen || !config.bounds.is_relative() {
+        return Ok(None);
+    }
+
+    display::at(orig).map(Some)
+}
+
+/// Clamps absolute `bounds` to the `desktop`, warning if they had to change,
+/// and fails if they are entirely off-screen.
+fn fit_bounds(label: &str, bounds: &mut Bounds, desktop: &Region) -> Result<()> {
+    if bounds.is_relative() {
+        return Ok(());
+    }
+    if let Bounds::Regions(regions) = bounds {
+        for (region, _) in regions {
+            let mut rect = Bounds::from(*region);
+            fit_bounds(label, &mut rect, desktop)?;
+            *region = rect.region(PointExt::new(0, 0), None);
+        }
+        return Ok(());
+    }
+
+    // absolute bounds don't depend on the origin
+    let region = bounds.region(PointExt::new(0, 0), None);
+    if !region.overlaps(desktop) {
+        bail!("{label} {bounds} are entirely off-screen");
+    }
+    let fitted = region.intersect(desktop);
+    if fitted != region {
+        let clamped = Bounds::from(fitted);
+        eprintln!("warning: {label} {bounds} extend off-screen, clamping to {clamped}");
+        *bounds = clamped;
+    }
+
+    Ok(())
+}
+
+/// Returns the area of the desktop at least `--margin` pixels from its outer
+/// edges, if a margin is set.
+fn safe_area(config: &Config) -> Result<Option<Region>> {
+    let Some(margin) = config.margin else {
+        return Ok(None);
+    };
+
+    let desktop = display::desktop()?;
+    let margin = margin as i32;
+    let region = Region {
+        x1: desktop.x1 + margin,
+        y1: desktop.y1 + margin,
+        x2: desktop.x2 - margin,
+        y2: desktop.y2 - margin,
+    };
+    if region.x1 > region.x2 || region.y1 > region.y2 {
+        bail!("margin of {margin}px leaves no room on the desktop");
+    }
+
+    Ok(Some(region))
+}
+
+/// Returns the command line options that give the same bounds as `region`,
+/// which is in the coordinates the cursor moves in.
+fn captured_flags(region: Region, config: &Config, monitor: Option<&Display>) -> String {
+    let displays = match monitor {
+        Some(_) => vec![],
+        // only informational, so fall back to the raw coordinates
+        None => display::all().unwrap_or_default(),
+    };
+    let from_cursor = |p: PointExt| match monitor {
+        Some(monitor) => monitor.offset_of(p, config.units),
+        None => display::from_cursor(p, config.units, &displays),
+    };
+    let region = region.map(from_cursor);
+
+    let mut flags = format!(
+        "--absolute-bounds {},{},{},{}",
+        region.x1, region.y1, region.x2, region.y2
+    );
+    if let Some(monitor) = &config.monitor {
+        flags.push_str(&format!(" --monitor {monitor}"));
+    }
+    if config.units != Units::default() {
+        flags.push_str(" --units logical");
+    }
+
+    flags
+}
+
+/// Returns `true` if `zones` cover absolute bounds entirely (or every region
+/// of them), leaving nowhere to move.
+fn is_fully_excluded(bounds: &Bounds, zones: &[Region]) -> bool {
+    match bounds {
+        // centered bounds are already absolute by the time this is checked
+        Bounds::Relative { .. }
+        | Bounds::Center { .. }
+        | Bounds::ActiveWindow { .. }
+        | Bounds::Window { .. } => false,
+        Bounds::Regions(regions) => regions.iter().all(|(r, _)| r.is_covered_by(zones)),
+        Bounds::Rect { .. } => bounds
+            .region(PointExt::new(0, 0), None)
+            .is_covered_by(zones),
+    }
+}
+
+/// Snaps `p` to the grid, unless that would put it in an excluded zone.
+fn snap(region: &Region, p: PointExt, grid: u32, zones: &[Region]) -> PointExt {
+    let snapped = region.snap(p, grid);
+    if source::is_excluded(zones, snapped) {
+        p
+    } else {
+        snapped
+    }
+}
+
+/// Shortens the movement from `pos` to `p` to at most `max` pixels, staying
+/// put instead if that would stop in an excluded zone.
+fn within(pos: PointExt, p: PointExt, max: u32, zones: &[Region]) -> PointExt {
+    let shortened = pos.within(p, max as f64);
+    if source::is_excluded(zones, shortened) {
+        pos
+    } else {
+        shortened
+    }
+}
+
+/// Returns the region points are picked from, kept inside `safe` if given.
+fn movement_region(
+    bounds: &Bounds,
+    orig: PointExt,
+    screen: Option<&Display>,
+    safe: Option<&Region>,
+) -> Region {
+    let region = bounds.region(orig, screen);
+    match safe {
+        Some(safe) => region.intersect(safe),
+        None => region,
+    }
+}
+
+/// Waits until the user unpauses, returning why to stop instead if they
+/// asked to quit or the deadline passed.
+fn pause(config: &Config, ui: &mut Ui) -> Result<Option<Exit>> {
+    loop {
+        if ui.is_past_deadline() {
+            return Ok(Some(Exit::Deadline));
+        }
+
+        ui.show(Status::Paused)?;
+        match KeyCommand::read(&Duration::from_secs(1))? {
+            KeyCommand::Quit => return Ok(Some(Exit::Quit)),
+            KeyCommand::TogglePause => {
+                input::debounce(config.input.debounce)?;
+                return Ok(None);
+            }
+            KeyCommand::ToggleHidden => {
+                input::debounce(config.input.debounce)?;
+                ui.toggle_hidden()?;
+            }
+            _ => (),
+        }
+    }
+}
+
+/// Counts down `delay` before the first movement, returning why to stop if
+/// waiting was cut short.
+fn warm_up(config: &Config, ui: &mut Ui, delay: Duration) -> Result<Option<Exit>> {
+    let start = Instant::now();
+    wait_while(config, ui, Duration::from_millis(250), || {
+        Ok(delay
+            .checked_sub(start.elapsed())
+            .filter(|remaining| !remaining.is_zero())
+            .map(|remaining| Status::StartingIn { remaining }))
+    })
+}
+
+/// Waits until the watchdog command succeeds, returning why to stop if
+/// waiting was cut short.
+fn wait_for_watchdog(
+    config: &Config,
+    ui: &mut Ui,
+    watchdog: &mut Watchdog,
+) -> Result<Option<Exit>> {
+    // short enough to pick up the result soon after the command exits
+    wait_while(config, ui, Duration::from_millis(250), || {
+        Ok((!watchdog.is_ok()?).then_some(Status::Watchdog))
+    })
+}
+
+/// Waits while the session is locked, firing the pause and resume hooks
+/// around it. Returns why to stop if `--on-lock` says to quit, or if waiting
+/// was cut short.
+fn wait_for_unlock(
+    config: &Config,
+    ui: &mut Ui,
+    hooks: &Hooks,
+    lock: &mut LockMonitor,
+) -> Result<Option<Exit>> {
+    if !lock.is_locked()? {
+        return Ok(None);
+    }
+    if config.on_lock == OnLock::Quit {
+        return Ok(Some(Exit::Locked));
+    }
+
+    hooks.fire(Event::Pause, Some("locked"))?;
+    let exit = wait_while(config, ui, Duration::from_secs(1), || {
+        Ok(lock.is_locked()?.then_some(Status::Locked))
+    })?;
+    if exit.is_some() {
+        return Ok(exit);
+    }
+    hooks.fire(Event::Resume, Some("unlocked"))?;
+
+    Ok(None)
+}
+
+/// Returns the bounds for the next movement, waiting while the window they
+/// follow is missing. Returns why to stop instead if waiting was cut short.
+fn wait_for_bounds(
+    config: &Config,
+    ui: &mut Ui,
+    rng: &fastrand::Rng,
+) -> Result<Result<Bounds, Exit>> {
+    let mut bounds = None;
+    let exit = wait_while(config, ui, Duration::from_secs(1), || {
+        match config.bounds.choose(rng) {
+            Ok(chosen) => {
+                bounds = Some(chosen);
+                Ok(None)
+            }
+            Err(e) if e.is::<window::NotFound>() => Ok(Some(Status::WaitingForWindow)),
+            Err(e) => Err(e),
+        }
+    })?;
+
+    Ok(match exit {
+        Some(exit) => Err(exit),
+        None => Ok(bounds.expect("waiting should only end once bounds were chosen")),
+    })
+}
+
+/// Waits until the next calendar day after the daily limit was reached,
+/// returning why to stop if waiting was cut short.
+fn wait_for_tomorrow(config: &Config, ui: &mut Ui, limit: Duration) -> Result<Option<Exit>> {
+    let day = today();
+    wait_while(config, ui, Duration::from_secs(1), || {
+        Ok((today() == day).then_some(Status::LimitReached { limit }))
+    })
+}
+
+/// Waits until the schedule is active, returning why to stop if waiting was
+/// cut short.
+fn wait_for_schedule(config: &Config, ui: &mut Ui, schedule: &Schedule) -> Result<Option<Exit>> {
+    // finding the next start can take a while for sparse cron expressions, so
+    // it's only searched for again once it has passed
+    let mut resumes = None;
+    wait_while(config, ui, Duration::from_secs(1), || {
+        let now = Local::now().naive_local();
+        if schedule.is_active(now) {
+            return Ok(None);
+        }
+        if resumes.is_none_or(|at| at <= now) {
+            resumes = schedule.next_start(now);
+        }
+
+        Ok(Some(Status::OffSchedule { resumes }))
+    })
+}
+
+fn today() -> NaiveDate {
+    Local::now().date_naive()
+}
+
+/// Waits until the system has been idle for `target`, returning why to stop
+/// if waiting was cut short.
+fn wait_for_idle(config: &Config, ui: &mut Ui, target: Duration) -> Result<Option<Exit>> {
+    wait_while(config, ui, Duration::from_millis(250), || {
+        let idle = idle::system_idle_time()?;
+        Ok(target
+            .checked_sub(idle)
+            .filter(|remaining| !remaining.is_zero())
+            .map(|remaining| Status::WaitingForIdle { remaining }))
+    })
+}
 
-        if result != last_p {
-            return result;
+/// Shows the status returned by `waiting` until it returns `None`, reading
+/// key commands every `poll` in between. Returns why to stop instead if the
+/// deadline passed or the user asked to quit while waiting.
+fn wait_while(
+    config: &Config,
+    ui: &mut Ui,
+    poll: Duration,
+    mut waiting: impl FnMut() -> Result<Option<Status>>,
+) -> Result<Option<Exit>> {
+    while let Some(status) = waiting()? {
+        if ui.is_past_deadline() {
+            return Ok(Some(Exit::Deadline));
+        }
+
+        ui.show(status)?;
+        match KeyCommand::read(&poll)? {
+            KeyCommand::Quit => return Ok(Some(Exit::Quit)),
+            KeyCommand::ToggleHidden => {
+                input::debounce(config.input.debounce)?;
+                ui.toggle_hidden()?;
+            }
+            _ => (),
         }
     }
+
+    Ok(None)
 }
 
-fn auto_pause(config: &Config, mouse: &MouseExt) -> Result<()> {
+fn auto_pause(config: &Config, mouse: &MouseExt, ui: &mut Ui) -> Result<()> {
     if !config.auto_pause {
         return Ok(());
     }
 
-    let mut stdout = stdout();
     let mut start = std::time::Instant::now();
     let mut elapsed = Duration::from_secs(0);
     let mut p = mouse
@@ -214,8 +1268,8 @@ fn auto_pause(config: &Config, mouse: &MouseExt) -> Result<()> {
 
     'countdown: while elapsed <= config.pause_interval {
         let remaining = config.pause_interval - elapsed;
-        print_auto_pause(&mut stdout, remaining);
-        if input::is_stdin_waiting(Duration::from_millis(80)) {
+        ui.show(Status::AutoPausing { remaining })?;
+        if input::is_stdin_waiting(config.input.auto_pause_poll) {
             break;
         }
 
@@ -227,10 +1281,12 @@ fn auto_pause(config: &Config, mouse: &MouseExt) -> Result<()> {
                 break 'reset;
             }
 
-            print_auto_pause(&mut stdout, config.pause_interval);
+            ui.show(Status::AutoPausing {
+                remaining: config.pause_interval,
+            })?;
 
             p = curr_pos;
-            if input::is_stdin_waiting(Duration::from_secs(2)) {
+            if input::is_stdin_waiting(config.input.auto_pause_reset) {
                 break 'countdown;
             }
             start = std::time::Instant::now();
@@ -240,44 +1296,3 @@ fn auto_pause(config: &Config, mouse: &MouseExt) -> Result<()> {
 
     Ok(())
 }
-
-fn print_auto_pause(stdout: &mut std::io::Stdout, remaining: Duration) {
-    let remaining_str = format!("{:.2}s", remaining.as_secs_f32());
-    execute!(
-        stdout,
-        Clear(ClearType::CurrentLine),
-        Print("Status:".bold().dim()),
-        Print(" auto-pausing for ".dim()),
-        SetForegroundColor(Color::Yellow),
-        Print(remaining_str),
-        ResetColor,
-        MoveToColumn(0),
-    )
-    .expect("should be able to write to stdout");
-}
-
-fn print_header(stdout: &mut std::io::Stdout) {
-    execute!(
-        stdout,
-        MoveTo(0, 0),
-        Print("Application started.".dim()),
-        MoveToNextLine(2),
-        Print("Commands".bold()),
-        MoveToNextLine(1),
-        Print("press ".dim()),
-        Print("q".bold()),
-        Print(" to quit".dim()),
-        MoveToNextLine(1),
-        Print("press ".dim()),
-        Print("p".bold()),
-        Print(" to toggle pause".dim()),
-        MoveToNextLine(1),
-        Print("press ".dim()),
-        Print("a".bold()),
-        Print(" to toggle animations".dim()),
-        MoveToNextLine(1),
-        Print("press any other key to skip an iteration".dim()),
-        MoveToNextLine(2),
-    )
-    .expect("should be able to write to stdout");
-}
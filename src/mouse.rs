@@ -1,7 +1,11 @@
-use crate::{animation, config::Config, input};
+use crate::animation::{self, Easing};
+use crate::backend::MouseBackend;
+use crate::bounds::Region;
+use crate::path::Path;
+use crate::trace::Trace;
+use crate::{config::Config, desktop, input};
 
 use mouse_rs::types::Point;
-use mouse_rs::Mouse;
 use thiserror::Error;
 
 use std::{
@@ -10,13 +14,44 @@ use std::{
 };
 
 const AUTO_PAUSE_TOLERANCE: f64 = 50.0;
+/// Width (in pixels) of the imaginary target aimed for with `--fitts`.
+const FITTS_TARGET_WIDTH: f64 = 20.;
+/// Fraction that Fitts' law durations randomly vary by either way.
+const FITTS_VARIATION: f64 = 0.15;
+/// How many noise waves a wobbling movement passes through.
+const WOBBLE_WAVES: f64 = 3.0;
+/// How many times per second the cursor drifts while hovering.
+const HOVER_WAVES_PER_SEC: f64 = 2.0;
+/// How long a micro-jiggle holds the cursor 1px away before returning it.
+const MICRO_HOLD: Duration = Duration::from_millis(10);
+const MICRO_STEPS: [(i32, i32); 4] = [(1, 0), (-1, 0), (0, 1), (0, -1)];
+/// Average seconds between simulated user movements.
+const SIMULATED_USER_PERIOD: f64 = 20.0;
+/// How far a simulated user movement pushes the cursor on each axis.
+const SIMULATED_USER_NUDGE: i32 = 150;
 
 #[derive(Debug, Error)]
 pub enum MouseError {
     #[error("mouse was in use")]
     Busy,
+    #[error("secure desktop is active")]
+    SecureDesktop,
+    #[error("cursor did not move when asked to")]
+    Unresponsive,
     #[error("internal error: {0}")]
-    InternalError(#[from] Box<dyn std::error::Error>),
+    InternalError(Box<dyn std::error::Error>),
+}
+
+impl From<Box<dyn std::error::Error>> for MouseError {
+    fn from(value: Box<dyn std::error::Error>) -> Self {
+        // injection always fails while the secure desktop is up, which isn't
+        // a real error as long as we wait for the user's desktop to return
+        if desktop::is_secure_desktop_active() {
+            return MouseError::SecureDesktop;
+        }
+
+        MouseError::InternalError(value)
+    }
 }
 
 #[derive(PartialEq, Eq, Clone, Copy)]
@@ -36,17 +71,42 @@ impl PointExt {
         Self { x, y }
     }
 
-    pub fn is_near(&self, p: Self, tolerance: f64) -> bool {
+    pub fn distance(&self, p: Self) -> f64 {
         f64::sqrt(f64::powi((self.x - p.x) as f64, 2) + f64::powi((self.y - p.y) as f64, 2))
-            < tolerance
     }
 
+    pub fn is_near(&self, p: Self, tolerance: f64) -> bool {
+        self.distance(p) < tolerance
+    }
+
+    /// Returns `p`, moved straight towards `self` if needed so it is at most
+    /// `max` pixels away.
+    pub fn within(&self, p: Self, max: f64) -> Self {
+        let distance = self.distance(p);
+        if distance <= max {
+            return p;
+        }
+
+        Self::lerp(*self, p, max / distance)
+    }
+
+    /// Interpolates between `p1` and `p2`.
+    ///
+    /// A `t` outside of `[0, 1]` continues past either end, so easing curves
+    /// can overshoot the target.
     pub fn lerp(p1: Self, p2: Self, t: f64) -> Self {
-        let t_clamp = t.clamp(0., 1.);
+        Self::new(
+            (p1.x as f64 + (p2.x - p1.x) as f64 * t).round() as i32,
+            (p1.y as f64 + (p2.y - p1.y) as f64 * t).round() as i32,
+        )
+    }
 
+    /// Interpolates along a cubic Hermite curve from `p1` to `p2`, leaving
+    /// `p1` with velocity `m1` and arriving at `p2` with velocity `m2`.
+    pub fn hermite(p1: Self, m1: (f64, f64), p2: Self, m2: (f64, f64), t: f64) -> Self {
         Self::new(
-            (p1.x as f64 + (p2.x - p1.x) as f64 * t_clamp).round() as i32,
-            (p1.y as f64 + (p2.y - p1.y) as f64 * t_clamp).round() as i32,
+            animation::hermite(p1.x as f64, m1.0, p2.x as f64, m2.0, t).round() as i32,
+            animation::hermite(p1.y as f64, m1.1, p2.y as f64, m2.1, t).round() as i32,
         )
     }
 }
@@ -61,46 +121,165 @@ impl From<Point> for PointExt {
 }
 
 pub struct MouseExt {
-    inner: Mouse,
-    interval: Duration,
+    inner: Box<dyn MouseBackend>,
     fps: u32,
     animate: bool,
     auto_pause: bool,
+    chain: bool,
+    max_accel: Option<f64>,
+    move_duration: Option<Duration>,
+    speed: Option<f64>,
+    fitts: Option<f64>,
+    easing: Easing,
+    wobble: f64,
+    curve: f64,
+    spline: bool,
+    dwell_jitter: Option<f64>,
+    rng: fastrand::Rng,
+    /// Velocity the last chained movement arrived with, in pixels per second.
+    velocity: (f64, f64),
+    simulated_user: Option<fastrand::Rng>,
 }
 
 impl MouseExt {
-    pub fn with_config(config: &Config) -> Self {
+    /// Starts the backend selected in `config` and moves the cursor with it.
+    pub fn with_config(config: &Config) -> anyhow::Result<Self> {
+        Ok(Self::new(config.backend.create()?, config))
+    }
+
+    /// Moves the cursor with `inner`, using the movement options in `config`.
+    pub fn new(inner: Box<dyn MouseBackend>, config: &Config) -> Self {
+        // anything but a straight line would leave a locked row or column
+        let straight = config.lock_axis.is_some();
+
         Self {
-            inner: Mouse::new(),
-            interval: config.interval,
+            inner,
             fps: config.fps,
             animate: config.animate,
             auto_pause: config.auto_pause,
+            chain: config.chain,
+            max_accel: config.max_accel,
+            move_duration: config.move_duration,
+            speed: config.speed,
+            fitts: config.fitts,
+            easing: config.easing,
+            wobble: if straight { 0. } else { config.wobble },
+            curve: if straight { 0. } else { config.curve },
+            spline: config.spline && !straight,
+            dwell_jitter: config.dwell_jitter.filter(|_| !straight),
+            rng: config
+                .seed
+                .map_or_else(fastrand::Rng::new, fastrand::Rng::with_seed),
+            velocity: (0., 0.),
+            simulated_user: None,
         }
     }
 
+    /// Randomly pushes the cursor away while moving, as if a user grabbed
+    /// it, so auto-pausing can be exercised without touching the mouse.
+    pub fn simulate_user(&mut self, rng: fastrand::Rng) {
+        self.simulated_user = Some(rng);
+    }
+
     #[inline]
     pub fn pos(&self) -> Result<PointExt, MouseError> {
-        Ok(self.inner.get_position()?.into())
+        Ok(self.inner.pos()?)
+    }
+
+    /// Verifies the cursor can actually be read and moved, by nudging it 1px
+    /// and reading the position back.
+    ///
+    /// The cursor is returned to its starting position afterwards.
+    pub fn self_test(&self) -> Result<(), MouseError> {
+        let start = self.pos()?;
+        let nudge = if start.x > 0 {
+            PointExt::new(start.x - 1, start.y)
+        } else {
+            PointExt::new(start.x + 1, start.y)
+        };
+
+        self.inner.move_to(nudge)?;
+        // some platforms don't report the new position immediately
+        spin_sleep::sleep(Duration::from_millis(50));
+        let moved = self.pos()? != start;
+        self.inner.move_to(start)?;
+
+        if !moved {
+            return Err(MouseError::Unresponsive);
+        }
+
+        Ok(())
     }
 
     pub fn toggle_animate(&mut self) {
         self.animate = !self.animate;
     }
 
-    pub fn move_to(&self, p: PointExt) -> Result<(), MouseError> {
+    /// Moves the cursor to `p`, animating over (or resting for) `interval`.
+    ///
+    /// With a move duration, speed, or Fitts' law difficulty set, the
+    /// animation takes that long (or as long as the distance needs) instead,
+    /// and the cursor rests for the remainder of `interval`.
+    pub fn move_to(&mut self, p: PointExt, interval: Duration) -> Result<(), MouseError> {
+        // the previous move's exit velocity only carries over if it finished,
+        // so clear it until this move completes
+        let start_velocity = std::mem::take(&mut self.velocity);
+
         if !self.animate {
-            return self.move_to_no_animate(p);
+            return self.move_to_no_animate(p, interval);
         }
 
         let frame_ms = 1000. / self.fps as f64;
         let frame_time = Duration::from_millis(frame_ms.round() as u64);
 
         let start_pos = self.pos()?;
+        let wobble_seed = self.rng.u64(..);
+        let path = if self.spline {
+            Path::spline(start_pos, p, &self.rng)
+        } else if self.curve > 0. {
+            Path::arc(start_pos, p, self.curve, &self.rng)
+        } else {
+            Path::line(start_pos, p)
+        };
         let mut last_pos = start_pos;
         let mut elapsed = Duration::from_secs(0);
 
-        while elapsed < self.interval {
+        // limited acceleration may need longer than the interval to arrive
+        let distance = start_pos.distance(p);
+        let travel = match (self.speed, self.fitts, self.move_duration) {
+            (Some(speed), _, _) => Duration::from_secs_f64(distance / speed),
+            (None, Some(difficulty), _) => {
+                let time = animation::fitts_time(distance, FITTS_TARGET_WIDTH, difficulty);
+                let variation = 1. + (self.rng.f64() * 2. - 1.) * FITTS_VARIATION;
+                Duration::from_secs_f64(time * variation)
+            }
+            (None, None, Some(move_duration)) => move_duration.min(interval),
+            (None, None, None) => interval,
+        };
+        let duration = match self.max_accel {
+            Some(accel) => travel.max(Duration::from_secs_f64(animation::min_travel_time(
+                distance, accel,
+            ))),
+            None => travel,
+        };
+
+        // hermite tangents are per unit of t, so velocities are scaled by how
+        // long each movement takes for the speed to carry over between them
+        let secs = duration.as_secs_f64();
+        let start_tangent = (start_velocity.0 * secs, start_velocity.1 * secs);
+        let end_tangent = ((p.x - start_pos.x) as f64, (p.y - start_pos.y) as f64);
+        // both ends are inside the bounds, so staying between them keeps the
+        // curve from overshooting past the bounds or the screen edge
+        let hull = Region::from([start_pos.x, start_pos.y, p.x, p.y]);
+
+        // nothing to animate over (e.g., a speed fast enough to cover the
+        // distance in under a nanosecond), so go straight to the end
+        if duration.is_zero() {
+            self.inner.move_to(p)?;
+            last_pos = self.pos()?;
+        }
+
+        while elapsed < duration {
             let f_start = Instant::now();
 
             // note: macOS `get_position` implementation seems to not update
@@ -113,14 +292,31 @@ impl MouseExt {
             }
 
             // interpolate the animation
-            let t = elapsed.as_millis() as f64 / self.interval.as_millis() as f64;
-            let new_pos = PointExt::lerp(start_pos, p, animation::ease_in_out(t));
+            let t = elapsed.as_secs_f64() / duration.as_secs_f64();
+            let new_pos = if self.chain {
+                let curve = PointExt::hermite(start_pos, start_tangent, p, end_tangent, t);
+                hull.clamp(curve)
+            } else if let Some(accel) = self.max_accel {
+                let s = animation::trapezoid(
+                    distance,
+                    accel,
+                    duration.as_secs_f64(),
+                    elapsed.as_secs_f64(),
+                );
+                path.at(s)
+            } else if self.speed.is_some() {
+                path.at(t)
+            } else {
+                path.at(self.easing.apply(t))
+            };
+            let new_pos = self.wobble(new_pos, start_pos, p, t, wobble_seed);
 
             // only update mouse if the position will change
             if new_pos != last_pos {
-                self.inner.move_to(new_pos.x, new_pos.y)?;
+                self.inner.move_to(new_pos)?;
                 last_pos = self.pos()?;
             }
+            self.nudge(frame_time)?;
 
             // pause for the remainder of frame time to achieve target fps
             let dt = f_start.elapsed();
@@ -135,14 +331,137 @@ impl MouseExt {
             elapsed += f_start.elapsed();
         }
 
+        // rest for whatever is left of the interval, still watching for the
+        // user taking over the mouse. resting stops the cursor, so only a
+        // movement that runs to the end of the interval keeps its velocity
+        let rest = interval.saturating_sub(elapsed);
+        if self.chain && rest.is_zero() && secs > 0. {
+            self.velocity = (end_tangent.0 / secs, end_tangent.1 / secs);
+        }
+        if rest.is_zero() || input::is_stdin_waiting(rest) {
+            return Ok(());
+        }
+        if self.auto_pause && !self.pos()?.is_near(last_pos, AUTO_PAUSE_TOLERANCE) {
+            return Err(MouseError::Busy);
+        }
+
+        Ok(())
+    }
+
+    /// Rests at the current position for `duration`, ending early if a key
+    /// command is waiting.
+    ///
+    /// With dwell jitter set, the cursor drifts by a pixel or two around the
+    /// position like a hand at rest, and returns to it afterwards.
+    pub fn dwell(&mut self, duration: Duration) -> Result<(), MouseError> {
+        // the next movement starts from rest
+        self.velocity = (0., 0.);
+
+        let Some(jitter) = self.dwell_jitter else {
+            input::is_stdin_waiting(duration);
+            return Ok(());
+        };
+
+        let frame_time = Duration::from_secs_f64(1. / self.fps as f64);
+        let center = self.pos()?;
+        let mut last_pos = center;
+        let (seed_x, seed_y) = (self.rng.u64(..), self.rng.u64(..));
+        let start = Instant::now();
+
+        while start.elapsed() < duration {
+            let f_start = Instant::now();
+            if self.auto_pause && !self.pos()?.is_near(last_pos, AUTO_PAUSE_TOLERANCE) {
+                return Err(MouseError::Busy);
+            }
+
+            let x = start.elapsed().as_secs_f64() * HOVER_WAVES_PER_SEC;
+            let new_pos = PointExt::new(
+                center.x + (jitter * animation::noise(x, seed_x)).round() as i32,
+                center.y + (jitter * animation::noise(x, seed_y)).round() as i32,
+            );
+            if new_pos != last_pos {
+                self.inner.move_to(new_pos)?;
+                last_pos = new_pos;
+            }
+
+            if input::is_stdin_waiting(frame_time.saturating_sub(f_start.elapsed())) {
+                break;
+            }
+        }
+
+        self.inner.move_to(center)?;
+
+        Ok(())
+    }
+
+    /// Plays back the positions in `trace` with their original timing,
+    /// shifted by `offset`.
+    pub fn play(&self, trace: &Trace, offset: (i32, i32)) -> Result<(), MouseError> {
+        let start = Instant::now();
+        let mut last_pos = None;
+
+        for sample in &trace.samples {
+            let at = Duration::from_millis(sample.ms);
+            let elapsed = start.elapsed();
+            if at > elapsed {
+                spin_sleep::sleep(at - elapsed);
+            }
+
+            if let Some(last_pos) = last_pos {
+                if self.auto_pause && !self.pos()?.is_near(last_pos, AUTO_PAUSE_TOLERANCE) {
+                    return Err(MouseError::Busy);
+                }
+            }
+
+            let p = PointExt::new(sample.x + offset.0, sample.y + offset.1);
+            if last_pos != Some(p) {
+                self.inner.move_to(p)?;
+                last_pos = Some(p);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Places the cursor at `p` without animating, unless the user moved it
+    /// away from `last` since it was placed there.
+    pub fn step_to(&self, last: PointExt, p: PointExt) -> Result<(), MouseError> {
+        if self.auto_pause && !self.pos()?.is_near(last, AUTO_PAUSE_TOLERANCE) {
+            return Err(MouseError::Busy);
+        }
+        self.inner.move_to(p)?;
+
+        Ok(())
+    }
+
+    /// Nudges the cursor 1px and straight back, so the screen looks the same
+    /// but the OS still sees input, then rests for `interval`.
+    pub fn micro(&self, interval: Duration) -> Result<(), MouseError> {
+        let start = self.pos()?;
+        let (dx, dy) = MICRO_STEPS[self.rng.usize(..MICRO_STEPS.len())];
+        self.inner
+            .move_to(PointExt::new(start.x + dx, start.y + dy))?;
+        spin_sleep::sleep(MICRO_HOLD);
+        self.inner.move_to(start)?;
+        self.nudge(interval)?;
+
+        if input::is_stdin_waiting(interval) {
+            return Ok(());
+        }
+
+        if self.auto_pause && !self.pos()?.is_near(start, AUTO_PAUSE_TOLERANCE) {
+            return Err(MouseError::Busy);
+        }
+
         Ok(())
     }
 
-    fn move_to_no_animate(&self, p: PointExt) -> Result<(), MouseError> {
-        self.inner.move_to(p.x, p.y)?;
+    fn move_to_no_animate(&self, p: PointExt, interval: Duration) -> Result<(), MouseError> {
+        self.inner.move_to(p)?;
+        self.nudge(interval)?;
 
         // make sure stdin isn't waiting while pausing
-        if input::is_stdin_waiting(self.interval) {
+        if input::is_stdin_waiting(interval) {
             return Ok(());
         }
 
@@ -152,4 +471,162 @@ impl MouseExt {
 
         Ok(())
     }
+
+    /// Offsets `pos` sideways from the path between `start` and `end` with
+    /// noise, fading in and out so the movement still starts and ends on
+    /// its endpoints.
+    fn wobble(&self, pos: PointExt, start: PointExt, end: PointExt, t: f64, seed: u64) -> PointExt {
+        let distance = start.distance(end);
+        if self.wobble <= 0. || distance == 0. {
+            return pos;
+        }
+
+        // noise is zero on whole numbers, so shift it by a random phase to
+        // avoid crossing the path at the same places on every movement
+        let phase = (seed % 1024) as f64 / 1024.;
+        let envelope = (std::f64::consts::PI * t.clamp(0., 1.)).sin();
+        let offset = self.wobble * envelope * animation::noise(t * WOBBLE_WAVES + phase, seed);
+        // unit vector perpendicular to the direction of travel
+        let (nx, ny) = (
+            -(end.y - start.y) as f64 / distance,
+            (end.x - start.x) as f64 / distance,
+        );
+
+        PointExt::new(
+            pos.x + (nx * offset).round() as i32,
+            pos.y + (ny * offset).round() as i32,
+        )
+    }
+
+    /// Moves the cursor away from where it was placed, with a chance based
+    /// on `elapsed`, when simulating a user.
+    fn nudge(&self, elapsed: Duration) -> Result<(), MouseError> {
+        let Some(rng) = &self.simulated_user else {
+            return Ok(());
+        };
+        if rng.f64() >= elapsed.as_secs_f64() / SIMULATED_USER_PERIOD {
+            return Ok(());
+        }
+
+        let p = self.pos()?;
+        let dx = if rng.bool() {
+            SIMULATED_USER_NUDGE
+        } else {
+            -SIMULATED_USER_NUDGE
+        };
+        let dy = if rng.bool() {
+            SIMULATED_USER_NUDGE
+        } else {
+            -SIMULATED_USER_NUDGE
+        };
+        self.inner.move_to(PointExt::new(p.x + dx, p.y + dy))?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::cell::{Cell, RefCell};
+    use std::error::Error;
+    use std::rc::Rc;
+
+    /// A cursor that only exists in memory, remembering every position it
+    /// was moved to. Setting `grab` makes the next movement land there
+    /// instead, as if the user took over the mouse.
+    #[derive(Clone, Default)]
+    struct MockBackend {
+        pos: Rc<Cell<(i32, i32)>>,
+        grab: Rc<Cell<Option<(i32, i32)>>>,
+        moves: Rc<RefCell<Vec<(i32, i32)>>>,
+    }
+
+    impl MockBackend {
+        fn pos(&self) -> PointExt {
+            let (x, y) = self.pos.get();
+            PointExt::new(x, y)
+        }
+    }
+
+    impl MouseBackend for MockBackend {
+        fn pos(&self) -> Result<PointExt, Box<dyn Error>> {
+            Ok(MockBackend::pos(self))
+        }
+
+        fn move_to(&self, p: PointExt) -> Result<(), Box<dyn Error>> {
+            self.moves.borrow_mut().push((p.x, p.y));
+            self.pos.set(self.grab.take().unwrap_or((p.x, p.y)));
+            Ok(())
+        }
+    }
+
+    fn mouse(config: &Config) -> (MouseExt, MockBackend) {
+        // there's no terminal to read key commands from
+        input::set_interactive(false);
+        let backend = MockBackend::default();
+        (MouseExt::new(Box::new(backend.clone()), config), backend)
+    }
+
+    #[test]
+    fn move_without_animation_lands_on_target() {
+        let config = Config {
+            animate: false,
+            ..Config::default()
+        };
+        let (mut mouse, backend) = mouse(&config);
+
+        let target = PointExt::new(120, 80);
+        assert!(mouse.move_to(target, Duration::from_millis(1)).is_ok());
+        assert!(backend.pos() == target);
+    }
+
+    #[test]
+    fn cursor_moved_by_user_is_busy() {
+        let config = Config {
+            animate: false,
+            ..Config::default()
+        };
+        let (mut mouse, backend) = mouse(&config);
+
+        backend.grab.set(Some((500, 500)));
+        let result = mouse.move_to(PointExt::new(10, 10), Duration::from_millis(1));
+        assert!(matches!(result, Err(MouseError::Busy)));
+    }
+
+    #[test]
+    fn cursor_moved_by_user_is_ignored_without_auto_pause() {
+        let config = Config {
+            animate: false,
+            auto_pause: false,
+            ..Config::default()
+        };
+        let (mut mouse, backend) = mouse(&config);
+
+        backend.grab.set(Some((500, 500)));
+        assert!(mouse
+            .move_to(PointExt::new(10, 10), Duration::from_millis(1))
+            .is_ok());
+    }
+
+    #[test]
+    fn chained_moves_do_not_overshoot() {
+        let config = Config {
+            chain: true,
+            ..Config::default()
+        };
+        let (mut mouse, backend) = mouse(&config);
+
+        // arriving at the corner moving right would carry the next movement
+        // past it without the curve being kept between the two ends
+        let interval = Duration::from_millis(150);
+        assert!(mouse.move_to(PointExt::new(100, 0), interval).is_ok());
+        assert!(mouse.move_to(PointExt::new(100, 100), interval).is_ok());
+
+        let moves = backend.moves.borrow();
+        assert!(moves
+            .iter()
+            .all(|&(x, y)| (0..=100).contains(&x) && (0..=100).contains(&y)));
+    }
 }
@@ -0,0 +1,139 @@
+use crate::mouse::PointExt;
+
+/// Fewest and most waypoints a spline passes through between its ends.
+const SPLINE_WAYPOINTS: std::ops::RangeInclusive<usize> = 2..=4;
+
+/// The route a movement takes between two points, separate from how fast it
+/// is travelled.
+pub enum Path {
+    Line {
+        start: PointExt,
+        end: PointExt,
+    },
+    /// A quadratic Bézier curve bending towards `control`.
+    Arc {
+        start: PointExt,
+        control: (f64, f64),
+        end: PointExt,
+    },
+    /// A Catmull-Rom spline passing through every point in order.
+    Spline {
+        points: Vec<(f64, f64)>,
+    },
+}
+
+impl Path {
+    pub fn line(start: PointExt, end: PointExt) -> Self {
+        Path::Line { start, end }
+    }
+
+    /// Returns an arc from `start` to `end` that bends to a random side by up
+    /// to `bend` times the distance between them.
+    pub fn arc(start: PointExt, end: PointExt, bend: f64, rng: &fastrand::Rng) -> Self {
+        let (dx, dy) = ((end.x - start.x) as f64, (end.y - start.y) as f64);
+
+        // pick a point somewhere along the middle of the line, then push it
+        // out perpendicular to the line
+        let along = 0.25 + rng.f64() * 0.5;
+        let offset = (rng.f64() * 2. - 1.) * bend;
+        let control = (
+            start.x as f64 + dx * along - dy * offset,
+            start.y as f64 + dy * along + dx * offset,
+        );
+
+        Path::Arc {
+            start,
+            control,
+            end,
+        }
+    }
+
+    /// Returns a spline from `start` to `end` through a few random waypoints
+    /// inside the rectangle between them, so it stays within the same bounds
+    /// as its ends.
+    pub fn spline(start: PointExt, end: PointExt, rng: &fastrand::Rng) -> Self {
+        let (dx, dy) = ((end.x - start.x) as f64, (end.y - start.y) as f64);
+        let mut waypoints: Vec<(f64, f64)> = (0..rng.usize(SPLINE_WAYPOINTS))
+            .map(|_| {
+                (
+                    start.x as f64 + dx * rng.f64(),
+                    start.y as f64 + dy * rng.f64(),
+                )
+            })
+            .collect();
+
+        // visit waypoints in the order they appear along the line, otherwise
+        // the path doubles back on itself
+        let progress = |p: &(f64, f64)| (p.0 - start.x as f64) * dx + (p.1 - start.y as f64) * dy;
+        waypoints.sort_by(|a, b| progress(a).total_cmp(&progress(b)));
+
+        let mut points = vec![(start.x as f64, start.y as f64)];
+        points.extend(waypoints);
+        points.push((end.x as f64, end.y as f64));
+
+        Path::Spline { points }
+    }
+
+    /// Returns the point `s` of the way along the path, where `s` outside of
+    /// `[0, 1]` continues past either end.
+    pub fn at(&self, s: f64) -> PointExt {
+        match *self {
+            Path::Line { start, end } => PointExt::lerp(start, end, s),
+            Path::Spline { ref points } => {
+                // each pair of points gets an equal share of `s`, and the
+                // first and last segments continue past the ends
+                let segments = points.len() - 1;
+                let scaled = s * segments as f64;
+                let i = (scaled.floor().max(0.) as usize).min(segments - 1);
+                let u = scaled - i as f64;
+
+                // mirror the neighbours of the end points so the curve
+                // leaves and arrives heading along the spline
+                let get = |j: isize| -> (f64, f64) {
+                    let last = segments as isize;
+                    if j < 0 {
+                        let (a, b) = (points[0], points[1]);
+                        (2. * a.0 - b.0, 2. * a.1 - b.1)
+                    } else if j > last {
+                        let (a, b) = (points[segments], points[segments - 1]);
+                        (2. * a.0 - b.0, 2. * a.1 - b.1)
+                    } else {
+                        points[j as usize]
+                    }
+                };
+                let i = i as isize;
+                let (p0, p1, p2, p3) = (get(i - 1), get(i), get(i + 1), get(i + 2));
+
+                PointExt::new(
+                    catmull_rom(p0.0, p1.0, p2.0, p3.0, u).round() as i32,
+                    catmull_rom(p0.1, p1.1, p2.1, p3.1, u).round() as i32,
+                )
+            }
+            Path::Arc {
+                start,
+                control,
+                end,
+            } => {
+                let a = (1. - s) * (1. - s);
+                let b = 2. * (1. - s) * s;
+                let c = s * s;
+                PointExt::new(
+                    (a * start.x as f64 + b * control.0 + c * end.x as f64).round() as i32,
+                    (a * start.y as f64 + b * control.1 + c * end.y as f64).round() as i32,
+                )
+            }
+        }
+    }
+}
+
+/// Evaluates one axis of a uniform Catmull-Rom segment from `p1` to `p2` at
+/// `u`, where `p0` and `p3` are the points either side.
+fn catmull_rom(p0: f64, p1: f64, p2: f64, p3: f64, u: f64) -> f64 {
+    let u2 = u * u;
+    let u3 = u2 * u;
+
+    0.5 * (2. * p1
+        + (p2 - p0) * u
+        + (2. * p0 - 5. * p1 + 4. * p2 - p3) * u2
+        + (3. * (p1 - p2) + p3 - p0) * u3)
+}
@@ -0,0 +1,151 @@
+use crate::bounds::Region;
+use crate::mouse::PointExt;
+
+use anyhow::{anyhow, Result};
+
+use std::f64::consts::TAU;
+use std::str::FromStr;
+
+/// Points around a circle before it repeats.
+const CIRCLE_STEPS: u64 = 12;
+/// Points around a figure eight before it repeats.
+const FIGURE8_STEPS: u64 = 16;
+/// Rows swept by a zigzag before it starts again from the top.
+const ZIGZAG_ROWS: u64 = 8;
+/// Largest step of a random walk, as a fraction of the region's smaller
+/// half-size.
+const WALK_STEP: f64 = 0.5;
+/// Order of the Hilbert curve, which splits the region into a grid of
+/// `2^order` cells on each side.
+const HILBERT_ORDER: u32 = 4;
+
+const SQUARE_CORNERS: [(f64, f64); 4] = [(-1., -1.), (1., -1.), (1., 1.), (-1., 1.)];
+
+/// Movement patterns that trace a shape in the bounds instead of picking
+/// points at random.
+#[derive(Debug, Clone, Copy)]
+pub enum Pattern {
+    Circle,
+    Figure8,
+    Square,
+    Zigzag,
+    RandomWalk,
+    Hilbert,
+}
+
+impl Pattern {
+    /// Returns the point at `step` of the pattern, scaled to fill `region`.
+    ///
+    /// Random walks wander from `last` instead, and Hilbert curves pick a
+    /// random point in each cell, so only they use `rng`.
+    pub fn point(
+        &self,
+        step: u64,
+        region: &Region,
+        last: PointExt,
+        rng: &fastrand::Rng,
+    ) -> PointExt {
+        let (cx, cy) = (
+            (region.x1 + region.x2) as f64 / 2.,
+            (region.y1 + region.y2) as f64 / 2.,
+        );
+        let (rx, ry) = (
+            (region.x2 - region.x1) as f64 / 2.,
+            (region.y2 - region.y1) as f64 / 2.,
+        );
+        // `u` and `v` between `[-1, 1]` span the region
+        let at = |u: f64, v: f64| {
+            PointExt::new((cx + rx * u).round() as i32, (cy + ry * v).round() as i32)
+        };
+
+        match self {
+            Pattern::Circle => {
+                let angle = TAU * (step % CIRCLE_STEPS) as f64 / CIRCLE_STEPS as f64;
+                at(angle.cos(), angle.sin())
+            }
+            Pattern::Figure8 => {
+                let angle = TAU * (step % FIGURE8_STEPS) as f64 / FIGURE8_STEPS as f64;
+                at(angle.sin(), (2. * angle).sin())
+            }
+            Pattern::Square => {
+                let (u, v) = SQUARE_CORNERS[(step % 4) as usize];
+                at(u, v)
+            }
+            Pattern::Zigzag => {
+                let row = step % ZIGZAG_ROWS;
+                let u = if step % 2 == 0 { -1. } else { 1. };
+                at(u, -1. + 2. * row as f64 / (ZIGZAG_ROWS - 1) as f64)
+            }
+            Pattern::RandomWalk => {
+                let reach = rx.min(ry).max(1.) * WALK_STEP;
+                let angle = rng.f64() * TAU;
+                let distance = rng.f64() * reach;
+                PointExt::new(
+                    ((last.x as f64 + angle.cos() * distance).round() as i32)
+                        .clamp(region.x1, region.x2),
+                    ((last.y as f64 + angle.sin() * distance).round() as i32)
+                        .clamp(region.y1, region.y2),
+                )
+            }
+            Pattern::Hilbert => {
+                let side = 1u64 << HILBERT_ORDER;
+                let (x, y) = hilbert_cell(step % (side * side), side);
+                // anywhere in the cell, so repeated passes don't retrace
+                // exactly the same points
+                let cell = 2. / side as f64;
+                at(
+                    -1. + cell * (x as f64 + rng.f64()),
+                    -1. + cell * (y as f64 + rng.f64()),
+                )
+            }
+        }
+    }
+}
+
+/// Returns the cell at distance `d` along a Hilbert curve through a grid of
+/// `side` by `side` cells, where `side` is a power of two.
+///
+/// Consecutive cells are always neighbors, so the curve covers the whole grid
+/// without long jumps.
+fn hilbert_cell(d: u64, side: u64) -> (u64, u64) {
+    let (mut x, mut y) = (0, 0);
+    let mut t = d;
+    let mut s = 1;
+    while s < side {
+        let rx = 1 & (t / 2);
+        let ry = 1 & (t ^ rx);
+        // rotate the quadrant so the curve connects to its neighbors
+        if ry == 0 {
+            if rx == 1 {
+                x = s - 1 - x;
+                y = s - 1 - y;
+            }
+            std::mem::swap(&mut x, &mut y);
+        }
+        x += s * rx;
+        y += s * ry;
+        t /= 4;
+        s *= 2;
+    }
+
+    (x, y)
+}
+
+impl FromStr for Pattern {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim() {
+            "circle" => Ok(Pattern::Circle),
+            "figure8" => Ok(Pattern::Figure8),
+            "square" => Ok(Pattern::Square),
+            "zigzag" => Ok(Pattern::Zigzag),
+            "random-walk" => Ok(Pattern::RandomWalk),
+            "hilbert" => Ok(Pattern::Hilbert),
+            s => Err(anyhow!(
+                "unknown pattern '{s}' (expected circle, figure8, square, zigzag, random-walk, or \
+                 hilbert)"
+            )),
+        }
+    }
+}
@@ -0,0 +1,86 @@
+use std::fmt;
+use std::time::Duration;
+
+/// An inclusive range of durations that can be randomly sampled from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DurationRange {
+    pub min: Duration,
+    pub max: Duration,
+}
+
+impl DurationRange {
+    pub fn new(min: Duration, max: Duration) -> Self {
+        Self { min, max }
+    }
+
+    /// Returns a random duration between `min` and `max` (inclusive).
+    pub fn sample(&self, rng: &fastrand::Rng) -> Duration {
+        if self.min >= self.max {
+            return self.min;
+        }
+
+        let min = self.min.as_nanos() as u64;
+        let max = self.max.as_nanos() as u64;
+        Duration::from_nanos(rng.u64(min..=max))
+    }
+
+    /// Returns this range with both ends multiplied by `factor`.
+    pub fn scale(&self, factor: f64) -> Self {
+        Self::new(self.min.mul_f64(factor), self.max.mul_f64(factor))
+    }
+}
+
+/// Randomly varies `duration` by up to `percent` in either direction.
+pub fn jitter(duration: Duration, percent: f64, rng: &fastrand::Rng) -> Duration {
+    let factor = 1. + (rng.f64() * 2. - 1.) * percent / 100.;
+
+    duration.mul_f64(factor.max(0.))
+}
+
+impl From<Duration> for DurationRange {
+    fn from(value: Duration) -> Self {
+        Self::new(value, value)
+    }
+}
+
+impl fmt::Display for DurationRange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.min == self.max {
+            return write!(f, "{:.2}s", self.min.as_secs_f32());
+        }
+
+        write!(
+            f,
+            "{:.2}s..{:.2}s",
+            self.min.as_secs_f32(),
+            self.max.as_secs_f32()
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn samples_stay_within_range() {
+        let range = DurationRange::new(Duration::from_millis(500), Duration::from_secs(2));
+        let rng = fastrand::Rng::with_seed(1);
+        for _ in 0..1000 {
+            let sample = range.sample(&rng);
+            assert!(
+                range.min <= sample && sample <= range.max,
+                "{sample:?} is out of range"
+            );
+        }
+    }
+
+    #[test]
+    fn single_duration_always_samples_itself() {
+        let range = DurationRange::from(Duration::from_secs(3));
+        let rng = fastrand::Rng::with_seed(1);
+        for _ in 0..10 {
+            assert_eq!(range.sample(&rng), Duration::from_secs(3));
+        }
+    }
+}
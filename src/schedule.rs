@@ -0,0 +1,354 @@
+use anyhow::{anyhow, ensure, Result};
+use chrono::{Datelike, Local, NaiveDateTime, NaiveTime, Timelike, Weekday};
+
+use std::str::FromStr;
+use std::time::Duration;
+
+/// How many days ahead to look for the next time a schedule becomes active.
+const LOOKAHEAD_DAYS: i64 = 366;
+
+/// Returns how long until the local clock next reads `time`.
+///
+/// Times that have already passed today are taken to mean tomorrow.
+pub fn until(time: NaiveTime) -> Duration {
+    let now = Local::now();
+    let mut date = now.date_naive();
+    if now.time() >= time {
+        date = date.succ_opt().expect("tomorrow should be a valid date");
+    }
+
+    let target = date.and_time(time);
+    let remaining = match target.and_local_timezone(Local).earliest() {
+        Some(target) => target - now,
+        // the time is skipped by a daylight saving change
+        None => target - now.naive_local(),
+    };
+
+    remaining.to_std().unwrap_or_default()
+}
+
+/// A daily window of local time, which may wrap past midnight (e.g.,
+/// 22:00-06:00).
+#[derive(Debug, Clone, Copy)]
+pub struct Hours {
+    pub start: NaiveTime,
+    pub end: NaiveTime,
+}
+
+impl Hours {
+    pub fn contains(&self, time: NaiveTime) -> bool {
+        if self.start <= self.end {
+            self.start <= time && time < self.end
+        } else {
+            time >= self.start || time < self.end
+        }
+    }
+}
+
+/// A set of days of the week.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Days([bool; 7]);
+
+impl Days {
+    pub fn insert(&mut self, day: Weekday) {
+        self.0[day.num_days_from_monday() as usize] = true;
+    }
+
+    pub fn contains(&self, day: Weekday) -> bool {
+        self.0[day.num_days_from_monday() as usize]
+    }
+}
+
+/// A cron expression (`MIN HOUR DAY MONTH WEEKDAY`), matching the minutes
+/// movements are allowed in.
+///
+/// Each field is `*` or a comma separated list of values and ranges, with an
+/// optional step (e.g., `*/5` or `9-17/2`). Weekdays are 0-7, where both 0
+/// and 7 are Sunday. As in cron, if both day fields are restricted then
+/// either one matching is enough.
+#[derive(Debug, Clone, Copy)]
+pub struct Cron {
+    minutes: u64,
+    hours: u64,
+    days: u64,
+    months: u64,
+    weekdays: u64,
+    any_day: bool,
+    any_weekday: bool,
+}
+
+impl Cron {
+    pub fn matches(&self, now: NaiveDateTime) -> bool {
+        let bit = |field: u64, value: u32| field & (1 << value) != 0;
+        let day = bit(self.days, now.day());
+        let weekday = bit(self.weekdays, now.weekday().num_days_from_sunday());
+        let day_matches = match (self.any_day, self.any_weekday) {
+            (false, false) => day || weekday,
+            _ => day && weekday,
+        };
+
+        bit(self.minutes, now.minute())
+            && bit(self.hours, now.hour())
+            && bit(self.months, now.month())
+            && day_matches
+    }
+}
+
+impl FromStr for Cron {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let fields = s.split_whitespace().collect::<Vec<_>>();
+        let [minutes, hours, days, months, weekdays] = fields[..] else {
+            return Err(anyhow!(
+                "expected 5 fields (minute, hour, day, month, weekday), found {}",
+                fields.len()
+            ));
+        };
+
+        let mut weekdays_mask = parse_cron_field(weekdays, 0, 7)?;
+        // 7 is an alias for Sunday
+        if weekdays_mask & (1 << 7) != 0 {
+            weekdays_mask |= 1;
+        }
+
+        Ok(Self {
+            minutes: parse_cron_field(minutes, 0, 59)?,
+            hours: parse_cron_field(hours, 0, 23)?,
+            days: parse_cron_field(days, 1, 31)?,
+            months: parse_cron_field(months, 1, 12)?,
+            weekdays: weekdays_mask,
+            // like cron, a field starting with `*` (e.g., `*/2`) doesn't
+            // restrict the day, so the other day field has to match too
+            any_day: days.starts_with('*'),
+            any_weekday: weekdays.starts_with('*'),
+        })
+    }
+}
+
+/// Parses a single cron field into a bit mask of the values it allows.
+fn parse_cron_field(field: &str, min: u32, max: u32) -> Result<u64> {
+    let mut mask = 0;
+    for part in field.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => {
+                let step = step
+                    .parse::<u32>()
+                    .map_err(|_| anyhow!("invalid step '{step}' in '{field}'"))?;
+                ensure!(step > 0, "step must be a positive number in '{field}'");
+                (range, step)
+            }
+            None => (part, 1),
+        };
+
+        let parse = |value: &str| -> Result<u32> {
+            let value = value
+                .parse::<u32>()
+                .map_err(|_| anyhow!("invalid value '{value}' in '{field}'"))?;
+            ensure!(
+                (min..=max).contains(&value),
+                "{value} is out of range in '{field}' (expected {min}-{max})"
+            );
+            Ok(value)
+        };
+        let (start, end) = match range {
+            "*" => (min, max),
+            _ => match range.split_once('-') {
+                Some((start, end)) => (parse(start)?, parse(end)?),
+                // a single value with a step runs to the end, like cron
+                None if step > 1 => (parse(range)?, max),
+                None => (parse(range)?, parse(range)?),
+            },
+        };
+        ensure!(start <= end, "range start is after range end in '{field}'");
+
+        for value in (start..=end).step_by(step as usize) {
+            mask |= 1 << value;
+        }
+    }
+
+    Ok(mask)
+}
+
+/// Limits movements to certain hours, days of the week, or cron matches.
+#[derive(Debug, Clone, Copy)]
+pub struct Schedule {
+    hours: Option<Hours>,
+    days: Option<Days>,
+    cron: Option<Cron>,
+}
+
+impl Schedule {
+    /// Returns a schedule, or `None` if nothing is limited.
+    pub fn new(hours: Option<Hours>, days: Option<Days>, cron: Option<Cron>) -> Option<Self> {
+        if hours.is_none() && days.is_none() && cron.is_none() {
+            return None;
+        }
+
+        Some(Self { hours, days, cron })
+    }
+
+    pub fn is_active(&self, now: NaiveDateTime) -> bool {
+        self.days.is_none_or(|days| days.contains(now.weekday()))
+            && self.hours.is_none_or(|hours| hours.contains(now.time()))
+            && self.cron.is_none_or(|cron| cron.matches(now))
+    }
+
+    /// Returns when the schedule next becomes active after `now`, if it
+    /// does within a year.
+    pub fn next_start(&self, now: NaiveDateTime) -> Option<NaiveDateTime> {
+        // schedules only change on minute boundaries
+        let mut next = now.with_second(0)?.with_nanosecond(0)? + chrono::Duration::minutes(1);
+        let end = now + chrono::Duration::days(LOOKAHEAD_DAYS);
+        while next < end {
+            if self.is_active(next) {
+                return Some(next);
+            }
+            next += chrono::Duration::minutes(1);
+        }
+
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(s: &str) -> NaiveDateTime {
+        NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M").expect("test times should be valid")
+    }
+
+    fn mask(values: impl IntoIterator<Item = u32>) -> u64 {
+        values.into_iter().fold(0, |mask, value| mask | 1 << value)
+    }
+
+    fn cron(s: &str) -> Cron {
+        s.parse().expect("test expressions should be valid")
+    }
+
+    #[test]
+    fn field_values_lists_and_ranges() {
+        assert_eq!(parse_cron_field("*", 1, 12).unwrap(), mask(1..=12));
+        assert_eq!(parse_cron_field("5", 0, 59).unwrap(), mask([5]));
+        assert_eq!(parse_cron_field("1,3,5", 0, 59).unwrap(), mask([1, 3, 5]));
+        assert_eq!(parse_cron_field("9-17", 0, 23).unwrap(), mask(9..=17));
+        assert_eq!(
+            parse_cron_field("1-3,10,20-21", 1, 31).unwrap(),
+            mask([1, 2, 3, 10, 20, 21])
+        );
+    }
+
+    #[test]
+    fn field_steps() {
+        assert_eq!(
+            parse_cron_field("*/15", 0, 59).unwrap(),
+            mask([0, 15, 30, 45])
+        );
+        assert_eq!(
+            parse_cron_field("*/2", 1, 31).unwrap(),
+            mask((1..=31).step_by(2))
+        );
+        assert_eq!(
+            parse_cron_field("9-17/2", 0, 23).unwrap(),
+            mask([9, 11, 13, 15, 17])
+        );
+        // a single value with a step runs to the end of the field
+        assert_eq!(parse_cron_field("5/20", 0, 59).unwrap(), mask([5, 25, 45]));
+    }
+
+    #[test]
+    fn field_errors() {
+        assert!(parse_cron_field("60", 0, 59).is_err());
+        assert!(parse_cron_field("0", 1, 31).is_err());
+        assert!(parse_cron_field("10-13", 1, 12).is_err());
+        assert!(parse_cron_field("8", 0, 7).is_err());
+        assert!(parse_cron_field("5-3", 0, 59).is_err());
+        assert!(parse_cron_field("*/0", 0, 59).is_err());
+        assert!(parse_cron_field("*/x", 0, 59).is_err());
+        assert!(parse_cron_field("-1", 0, 59).is_err());
+        assert!(parse_cron_field("", 0, 59).is_err());
+    }
+
+    #[test]
+    fn seven_is_sunday() {
+        let sunday = cron("0 0 * * 7");
+        assert_eq!(sunday.weekdays & 1, 1);
+        // 2024-06-02 is a Sunday
+        assert!(sunday.matches(at("2024-06-02 00:00")));
+        assert!(!sunday.matches(at("2024-06-03 00:00")));
+    }
+
+    #[test]
+    fn wrong_number_of_fields() {
+        assert!("* * * *".parse::<Cron>().is_err());
+        assert!("* * * * * *".parse::<Cron>().is_err());
+    }
+
+    #[test]
+    fn matches_every_field() {
+        let work = cron("*/15 9-17 * * 1-5");
+        // 2024-06-03 is a Monday
+        assert!(work.matches(at("2024-06-03 09:30")));
+        assert!(!work.matches(at("2024-06-03 09:31")));
+        assert!(!work.matches(at("2024-06-03 18:00")));
+        assert!(!work.matches(at("2024-06-01 09:30")));
+
+        let june = cron("0 12 * 6 *");
+        assert!(june.matches(at("2024-06-15 12:00")));
+        assert!(!june.matches(at("2024-07-15 12:00")));
+    }
+
+    #[test]
+    fn restricted_day_fields_match_either() {
+        // any friday, or the 13th of any month
+        let cron = cron("0 0 13 * 5");
+        assert!(cron.matches(at("2024-06-07 00:00")));
+        assert!(cron.matches(at("2024-06-13 00:00")));
+        assert!(!cron.matches(at("2024-06-12 00:00")));
+    }
+
+    #[test]
+    fn stepped_star_day_fields_match_both() {
+        // odd days that are also mondays
+        let cron = cron("0 0 */2 * 1");
+        assert!(cron.matches(at("2024-06-03 00:00")));
+        assert!(!cron.matches(at("2024-06-10 00:00")));
+        assert!(!cron.matches(at("2024-06-05 00:00")));
+    }
+
+    #[test]
+    fn next_start_with_cron() {
+        let schedule = Schedule::new(None, None, Some(cron("0 9 * * 1-5"))).unwrap();
+        // from saturday afternoon to monday morning
+        assert_eq!(
+            schedule.next_start(at("2024-06-01 12:00")),
+            Some(at("2024-06-03 09:00"))
+        );
+    }
+
+    #[test]
+    fn next_start_with_hours_and_days() {
+        let hours = Hours {
+            start: NaiveTime::from_hms_opt(22, 0, 0).unwrap(),
+            end: NaiveTime::from_hms_opt(6, 0, 0).unwrap(),
+        };
+        let mut days = Days::default();
+        days.insert(Weekday::Tue);
+        let schedule = Schedule::new(Some(hours), Some(days), None).unwrap();
+
+        let now = at("2024-06-03 12:30") + chrono::Duration::seconds(45);
+        assert_eq!(schedule.next_start(now), Some(at("2024-06-04 00:00")));
+        assert_eq!(
+            schedule.next_start(at("2024-06-04 12:00")),
+            Some(at("2024-06-04 22:00"))
+        );
+    }
+
+    #[test]
+    fn next_start_gives_up_after_a_year() {
+        // february 30th never happens
+        let schedule = Schedule::new(None, None, Some(cron("0 0 30 2 *"))).unwrap();
+        assert_eq!(schedule.next_start(at("2024-06-01 12:00")), None);
+    }
+}
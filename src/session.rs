@@ -0,0 +1,160 @@
+use anyhow::{anyhow, Result};
+use clap::builder::PossibleValue;
+use clap::ValueEnum;
+use serde::Deserialize;
+
+use std::time::{Duration, Instant};
+
+/// How often to ask the OS whether the session is locked.
+const CHECK_INTERVAL: Duration = Duration::from_secs(2);
+
+/// What to do while the session is locked.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum OnLock {
+    /// Keep moving as if nothing happened.
+    #[default]
+    Continue,
+    /// Stop moving until the session is unlocked.
+    Pause,
+    /// Exit the application.
+    Quit,
+}
+
+impl ValueEnum for OnLock {
+    fn value_variants<'a>() -> &'a [Self] {
+        &[OnLock::Continue, OnLock::Pause, OnLock::Quit]
+    }
+
+    fn to_possible_value(&self) -> Option<PossibleValue> {
+        Some(match self {
+            OnLock::Continue => PossibleValue::new("continue").help("Keep moving"),
+            OnLock::Pause => PossibleValue::new("pause").help("Stop moving until unlocked"),
+            OnLock::Quit => PossibleValue::new("quit").help("Exit"),
+        })
+    }
+}
+
+/// Watches for the session being locked, asking the OS at most every
+/// [`CHECK_INTERVAL`] since it can mean running a command.
+pub struct LockMonitor {
+    last_check: Option<Instant>,
+    locked: bool,
+}
+
+impl LockMonitor {
+    pub fn new() -> Self {
+        Self {
+            last_check: None,
+            locked: false,
+        }
+    }
+
+    /// Returns `true` if the session was locked when last checked, checking
+    /// again first if the check interval has elapsed.
+    pub fn is_locked(&mut self) -> Result<bool> {
+        let due = self
+            .last_check
+            .is_none_or(|last| last.elapsed() >= CHECK_INTERVAL);
+        if due {
+            self.locked = is_locked()
+                .map_err(|e| anyhow!("failed to check whether the session is locked ({e})"))?;
+            self.last_check = Some(Instant::now());
+        }
+
+        Ok(self.locked)
+    }
+}
+
+/// Returns `true` if the current session is locked, from the WTS session
+/// flags.
+#[cfg(windows)]
+fn is_locked() -> Result<bool> {
+    use anyhow::ensure;
+    use windows_sys::Win32::System::RemoteDesktop::{
+        WTSFreeMemory, WTSQuerySessionInformationW, WTSSessionInfoEx, WTSINFOEXW,
+        WTS_CURRENT_SERVER_HANDLE, WTS_CURRENT_SESSION, WTS_SESSIONSTATE_LOCK,
+    };
+
+    let mut buffer = std::ptr::null_mut();
+    let mut len = 0;
+    // SAFETY: the buffer is only read if the call succeeded, and is freed
+    // right after
+    unsafe {
+        ensure!(
+            WTSQuerySessionInformationW(
+                WTS_CURRENT_SERVER_HANDLE,
+                WTS_CURRENT_SESSION,
+                WTSSessionInfoEx,
+                &mut buffer,
+                &mut len,
+            ) != 0,
+            "{}",
+            std::io::Error::last_os_error()
+        );
+        let info = &*(buffer as *const WTSINFOEXW);
+        let locked = info.Data.WTSInfoExLevel1.SessionFlags as u32 == WTS_SESSIONSTATE_LOCK;
+        WTSFreeMemory(buffer.cast());
+
+        Ok(locked)
+    }
+}
+
+/// Returns `true` if the current session is locked, from the Core Graphics
+/// session dictionary.
+#[cfg(target_os = "macos")]
+fn is_locked() -> Result<bool> {
+    use core_foundation::base::{CFType, TCFType};
+    use core_foundation::boolean::CFBoolean;
+    use core_foundation::dictionary::{CFDictionary, CFDictionaryRef};
+    use core_foundation::string::CFString;
+
+    #[link(name = "CoreGraphics", kind = "framework")]
+    extern "C" {
+        fn CGSessionCopyCurrentDictionary() -> CFDictionaryRef;
+    }
+
+    // SAFETY: the dictionary is checked before use, and is released when
+    // dropped since this function owns it
+    let session: CFDictionary<CFString, CFType> = unsafe {
+        let dict = CGSessionCopyCurrentDictionary();
+        if dict.is_null() {
+            return Err(anyhow!("there is no window server session"));
+        }
+        TCFType::wrap_under_create_rule(dict)
+    };
+
+    // the key is only present while the screen is locked
+    Ok(session
+        .find(CFString::from_static_string("CGSSessionScreenIsLocked"))
+        .and_then(|value| value.downcast::<CFBoolean>())
+        .is_some_and(bool::from))
+}
+
+/// Returns `true` if the current session is locked, from logind's
+/// `LockedHint` (set by screen lockers that integrate with it).
+#[cfg(all(unix, not(target_os = "macos")))]
+fn is_locked() -> Result<bool> {
+    use anyhow::ensure;
+    use std::process::Command;
+
+    // "auto" is the session of this process, or the user's graphical one
+    // when running outside of any (e.g., over SSH)
+    let session = std::env::var("XDG_SESSION_ID").unwrap_or_else(|_| "auto".into());
+    let output = Command::new("loginctl")
+        .args(["show-session", &session, "--property=LockedHint", "--value"])
+        .output()
+        .map_err(|e| anyhow!("failed to run loginctl ({e})"))?;
+    ensure!(
+        output.status.success(),
+        "loginctl failed: {}",
+        String::from_utf8_lossy(&output.stderr).trim()
+    );
+
+    Ok(String::from_utf8_lossy(&output.stdout).trim() == "yes")
+}
+
+#[cfg(not(any(windows, unix)))]
+fn is_locked() -> Result<bool> {
+    Err(anyhow!("lock detection isn't supported on this platform"))
+}
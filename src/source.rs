@@ -0,0 +1,410 @@
+use crate::bounds::Region;
+use crate::config::Config;
+use crate::mouse::PointExt;
+use crate::pattern::Pattern;
+
+use anyhow::{anyhow, bail, ensure, Result};
+
+use std::collections::VecDeque;
+use std::f64::consts::TAU;
+use std::fs;
+use std::path::Path;
+
+/// Normal samples to draw before giving up on landing inside the region, and
+/// clamping the last one instead.
+const GAUSSIAN_ATTEMPTS: usize = 100;
+
+/// Samples to draw before giving up on avoiding recent points, and using the
+/// last one anyway.
+const AVOID_ATTEMPTS: usize = 50;
+
+/// Samples to draw before giving up on the source and picking a point outside
+/// the excluded zones directly.
+const EXCLUDE_ATTEMPTS: usize = 50;
+
+/// Strategies for picking where the cursor moves next.
+pub trait PointSource {
+    /// Returns the next point to move to, usually inside `region`, where
+    /// `last` is the point the cursor last moved to.
+    fn next(&mut self, region: &Region, last: PointExt, rng: &fastrand::Rng) -> PointExt;
+}
+
+/// Returns the point source selected by `config`.
+pub fn from_config(config: &Config) -> Result<Box<dyn PointSource>> {
+    if let Some(path) = &config.waypoints {
+        let waypoints = Waypoints::load(path, config.shuffle_waypoints)?;
+        // fixed points can't be redrawn, so they have to be valid up front
+        if let Some(p) = waypoints
+            .points
+            .iter()
+            .find(|p| is_excluded(&config.exclude, **p))
+        {
+            bail!("waypoint {},{} is inside an excluded zone", p.x, p.y);
+        }
+        return Ok(Box::new(waypoints));
+    }
+
+    let mut source: Box<dyn PointSource> = match (config.pattern, config.gaussian) {
+        (Some(pattern), _) => Box::new(PatternSource::new(pattern)),
+        (None, Some(sigma)) => Box::new(Gaussian { sigma }),
+        (None, None) => Box::new(Uniform),
+    };
+    // patterns have a fixed order, so there is nothing to avoid
+    if let (Some(radius), None) = (config.avoid_radius, config.pattern) {
+        source = Box::new(AvoidRecent::new(source, radius as f64, config.avoid_count));
+    }
+
+    if !config.exclude.is_empty() {
+        source = Box::new(Exclude {
+            inner: source,
+            zones: config.exclude.clone(),
+        });
+    }
+
+    Ok(source)
+}
+
+/// Returns `true` if `p` is inside any of `zones`.
+pub fn is_excluded(zones: &[Region], p: PointExt) -> bool {
+    zones.iter().any(|zone| zone.contains(p))
+}
+
+/// Picks points anywhere in the region with equal probability.
+pub struct Uniform;
+
+impl PointSource for Uniform {
+    fn next(&mut self, region: &Region, last: PointExt, rng: &fastrand::Rng) -> PointExt {
+        // a single pixel region can only ever give the same point
+        if region.x1 == region.x2 && region.y1 == region.y2 {
+            return PointExt::new(region.x1, region.y1);
+        }
+
+        loop {
+            let result = PointExt {
+                x: rng.i32(region.x1..=region.x2),
+                y: rng.i32(region.y1..=region.y2),
+            };
+
+            if result != last {
+                return result;
+            }
+        }
+    }
+}
+
+/// Picks points from a 2D normal distribution around the middle of the
+/// region, which is the starting position for relative bounds.
+pub struct Gaussian {
+    /// Standard deviation in pixels.
+    sigma: f64,
+}
+
+impl PointSource for Gaussian {
+    fn next(&mut self, region: &Region, last: PointExt, rng: &fastrand::Rng) -> PointExt {
+        if region.x1 == region.x2 && region.y1 == region.y2 {
+            return PointExt::new(region.x1, region.y1);
+        }
+
+        let (cx, cy) = (
+            (region.x1 + region.x2) as f64 / 2.,
+            (region.y1 + region.y2) as f64 / 2.,
+        );
+        let mut result = last;
+        for _ in 0..GAUSSIAN_ATTEMPTS {
+            // Box-Muller transform, avoiding ln(0)
+            let radius = self.sigma * (-2. * (1. - rng.f64()).ln()).sqrt();
+            let angle = TAU * rng.f64();
+            result = PointExt::new(
+                (cx + radius * angle.cos()).round() as i32,
+                (cy + radius * angle.sin()).round() as i32,
+            );
+
+            let inside = (region.x1..=region.x2).contains(&result.x)
+                && (region.y1..=region.y2).contains(&result.y);
+            if inside && result != last {
+                return result;
+            }
+        }
+
+        PointExt::new(
+            result.x.clamp(region.x1, region.x2),
+            result.y.clamp(region.y1, region.y2),
+        )
+    }
+}
+
+/// Redraws points from another source that land within `radius` of any of
+/// the last few points, so movements spread out over the bounds.
+pub struct AvoidRecent {
+    inner: Box<dyn PointSource>,
+    radius: f64,
+    recent: VecDeque<PointExt>,
+    count: usize,
+}
+
+impl AvoidRecent {
+    pub fn new(inner: Box<dyn PointSource>, radius: f64, count: usize) -> Self {
+        Self {
+            inner,
+            radius,
+            recent: VecDeque::with_capacity(count),
+            count,
+        }
+    }
+}
+
+impl PointSource for AvoidRecent {
+    fn next(&mut self, region: &Region, last: PointExt, rng: &fastrand::Rng) -> PointExt {
+        let mut p = self.inner.next(region, last, rng);
+        for _ in 1..AVOID_ATTEMPTS {
+            if !self.recent.iter().any(|r| r.is_near(p, self.radius)) {
+                break;
+            }
+            p = self.inner.next(region, last, rng);
+        }
+
+        if self.recent.len() >= self.count {
+            self.recent.pop_front();
+        }
+        self.recent.push_back(p);
+        p
+    }
+}
+
+/// Redraws points from another source that land inside any of the excluded
+/// zones, so the cursor never stops on them.
+pub struct Exclude {
+    inner: Box<dyn PointSource>,
+    zones: Vec<Region>,
+}
+
+impl PointSource for Exclude {
+    fn next(&mut self, region: &Region, last: PointExt, rng: &fastrand::Rng) -> PointExt {
+        for _ in 0..EXCLUDE_ATTEMPTS {
+            let p = self.inner.next(region, last, rng);
+            if !is_excluded(&self.zones, p) {
+                return p;
+            }
+        }
+
+        // the source keeps landing in a zone (e.g., a narrow gaussian over
+        // it), so fall back to anywhere else, or stay put if nowhere is left
+        region.free_point(&self.zones, rng).unwrap_or(last)
+    }
+}
+
+/// Steps through the points of a [`Pattern`] in order.
+pub struct PatternSource {
+    pattern: Pattern,
+    step: u64,
+}
+
+impl PatternSource {
+    pub fn new(pattern: Pattern) -> Self {
+        Self { pattern, step: 0 }
+    }
+}
+
+impl PointSource for PatternSource {
+    fn next(&mut self, region: &Region, last: PointExt, rng: &fastrand::Rng) -> PointExt {
+        let p = self.pattern.point(self.step, region, last, rng);
+        self.step += 1;
+        p
+    }
+}
+
+/// Cycles through a fixed list of absolute points, ignoring the bounds.
+pub struct Waypoints {
+    points: Vec<PointExt>,
+    index: usize,
+    shuffle: bool,
+}
+
+impl Waypoints {
+    /// Reads points from a file with one `X,Y` pair per line. Blank lines and
+    /// lines starting with `#` are skipped.
+    ///
+    /// With `shuffle`, the points are visited in a new random order on every
+    /// pass through the list.
+    pub fn load(path: &Path, shuffle: bool) -> Result<Self> {
+        let contents = fs::read_to_string(path)
+            .map_err(|e| anyhow!("failed to read waypoints file {} ({e})", path.display()))?;
+
+        let mut points = vec![];
+        for (i, line) in contents.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let point =
+                parse_point(line).map_err(|e| anyhow!("{}:{}: {e}", path.display(), i + 1))?;
+            points.push(point);
+        }
+        ensure!(
+            !points.is_empty(),
+            "waypoints file {} has no points",
+            path.display()
+        );
+
+        Ok(Self {
+            points,
+            index: 0,
+            shuffle,
+        })
+    }
+}
+
+impl PointSource for Waypoints {
+    fn next(&mut self, _region: &Region, _last: PointExt, rng: &fastrand::Rng) -> PointExt {
+        if self.index == 0 && self.shuffle {
+            rng.shuffle(&mut self.points);
+        }
+        let p = self.points[self.index];
+        self.index = (self.index + 1) % self.points.len();
+        p
+    }
+}
+
+/// Parses a point written as `X,Y` (spaces around either value are allowed).
+fn parse_point(s: &str) -> Result<PointExt> {
+    let (x, y) = s
+        .split_once(',')
+        .ok_or_else(|| anyhow!("expected a point as X,Y, found '{s}'"))?;
+    let x = x
+        .trim()
+        .parse()
+        .map_err(|e| anyhow!("invalid x coordinate '{}' ({e})", x.trim()))?;
+    let y = y
+        .trim()
+        .parse()
+        .map_err(|e| anyhow!("invalid y coordinate '{}' ({e})", y.trim()))?;
+
+    Ok(PointExt::new(x, y))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLES: usize = 1000;
+
+    fn region() -> Region {
+        Region::from([100, 200, 400, 350])
+    }
+
+    fn waypoints(shuffle: bool) -> Waypoints {
+        Waypoints {
+            points: (0..8).map(|i| PointExt::new(i, i * 10)).collect(),
+            index: 0,
+            shuffle,
+        }
+    }
+
+    fn stays_inside(mut source: impl PointSource) {
+        let (region, rng) = (region(), fastrand::Rng::with_seed(1));
+        let mut last = PointExt::new(250, 275);
+        for _ in 0..SAMPLES {
+            last = source.next(&region, last, &rng);
+            assert!(region.contains(last), "{last} is outside the region");
+        }
+    }
+
+    #[test]
+    fn uniform_stays_inside_region() {
+        stays_inside(Uniform);
+    }
+
+    #[test]
+    fn gaussian_stays_inside_region() {
+        stays_inside(Gaussian { sigma: 40. });
+        // most samples miss the region, so these are clamped
+        stays_inside(Gaussian { sigma: 5000. });
+    }
+
+    #[test]
+    fn single_pixel_region_gives_that_pixel() {
+        let (region, rng) = (Region::from([7, 9, 7, 9]), fastrand::Rng::with_seed(1));
+        let pixel = PointExt::new(7, 9);
+
+        assert!(Uniform.next(&region, pixel, &rng) == pixel);
+        assert!(Gaussian { sigma: 10. }.next(&region, pixel, &rng) == pixel);
+    }
+
+    #[test]
+    fn avoid_recent_keeps_away_from_recent_points() {
+        let (region, rng) = (region(), fastrand::Rng::with_seed(1));
+        let (radius, count) = (30., 4);
+        let mut source = AvoidRecent::new(Box::new(Uniform), radius, count);
+
+        let mut recent: VecDeque<PointExt> = VecDeque::new();
+        let mut last = PointExt::new(250, 275);
+        for _ in 0..SAMPLES {
+            last = source.next(&region, last, &rng);
+            assert!(
+                !recent.iter().any(|r| r.is_near(last, radius)),
+                "{last} is within {radius} of a recent point"
+            );
+
+            if recent.len() >= count {
+                recent.pop_front();
+            }
+            recent.push_back(last);
+        }
+    }
+
+    #[test]
+    fn exclude_never_lands_in_a_zone() {
+        let (region, rng) = (region(), fastrand::Rng::with_seed(1));
+        // covers most of the region, so the fallback gets used too
+        let zones = vec![
+            Region::from([100, 200, 380, 350]),
+            Region::from([390, 200, 400, 300]),
+        ];
+        let mut source = Exclude {
+            inner: Box::new(Uniform),
+            zones: zones.clone(),
+        };
+
+        let mut last = PointExt::new(395, 340);
+        for _ in 0..SAMPLES {
+            last = source.next(&region, last, &rng);
+            assert!(!is_excluded(&zones, last), "{last} is inside a zone");
+        }
+    }
+
+    #[test]
+    fn waypoints_follow_their_order() {
+        let (region, rng) = (region(), fastrand::Rng::with_seed(1));
+        let mut source = waypoints(false);
+        let expected = source.points.clone();
+
+        let mut last = PointExt::new(0, 0);
+        for i in 0..expected.len() * 3 {
+            last = source.next(&region, last, &rng);
+            assert!(last == expected[i % expected.len()]);
+        }
+    }
+
+    #[test]
+    fn shuffled_waypoints_are_deterministic_under_a_seed() {
+        let region = region();
+        let visit = |seed| {
+            let rng = fastrand::Rng::with_seed(seed);
+            let mut source = waypoints(true);
+            let last = PointExt::new(0, 0);
+            (0..source.points.len() * 2)
+                .map(|_| source.next(&region, last, &rng))
+                .map(|p| (p.x, p.y))
+                .collect::<Vec<_>>()
+        };
+
+        let visited = visit(42);
+        assert_eq!(visited, visit(42));
+
+        // every pass still visits each point once
+        let mut pass = visited[..8].to_vec();
+        pass.sort_unstable();
+        let all = (0..8).map(|i| (i, i * 10)).collect::<Vec<_>>();
+        assert_eq!(pass, all);
+    }
+}
@@ -0,0 +1,85 @@
+use anyhow::{anyhow, Result};
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+use std::fs;
+use std::io::ErrorKind;
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// Application state that is kept across restarts.
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct State {
+    pub paused: bool,
+    pub usage: Usage,
+}
+
+/// How long the application was active on a given day.
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Usage {
+    pub date: Option<NaiveDate>,
+    pub active_secs: f64,
+}
+
+impl Usage {
+    /// Returns how long the application has been active on `today`.
+    pub fn active(&self, today: NaiveDate) -> Duration {
+        if self.date != Some(today) {
+            return Duration::ZERO;
+        }
+
+        Duration::from_secs_f64(self.active_secs.max(0.))
+    }
+
+    /// Adds `elapsed` to the active time for `today`, starting over if the
+    /// last recorded usage was on a different day.
+    pub fn add(&mut self, today: NaiveDate, elapsed: Duration) {
+        if self.date != Some(today) {
+            self.date = Some(today);
+            self.active_secs = 0.;
+        }
+        self.active_secs += elapsed.as_secs_f64();
+    }
+}
+
+impl State {
+    /// Returns the location of the state file, if the platform has a
+    /// suitable directory for it.
+    pub fn path() -> Option<PathBuf> {
+        dirs::state_dir()
+            .or_else(dirs::data_local_dir)
+            .map(|dir| dir.join("mouse-jiggler").join("state.toml"))
+    }
+
+    /// Loads the state file, or returns the default state if it doesn't
+    /// exist yet.
+    pub fn load() -> Result<Self> {
+        let Some(path) = Self::path() else {
+            return Ok(Self::default());
+        };
+
+        match fs::read_to_string(&path) {
+            Ok(contents) => toml::from_str(&contents)
+                .map_err(|e| anyhow!("failed to parse state file {} ({e})", path.display())),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(anyhow!(
+                "failed to read state file {} ({e})",
+                path.display()
+            )),
+        }
+    }
+
+    pub fn save(&self) -> Result<()> {
+        let Some(path) = Self::path() else {
+            return Ok(());
+        };
+
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(&path, toml::to_string(self)?)
+            .map_err(|e| anyhow!("failed to write state file {} ({e})", path.display()))
+    }
+}
@@ -0,0 +1,65 @@
+use clap::builder::PossibleValue;
+use clap::ValueEnum;
+
+use std::time::Duration;
+
+/// Applications with a known idle timeout that a preset can be chosen for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Target {
+    Teams,
+    Slack,
+    Rdp,
+    Screensaver,
+}
+
+impl Target {
+    /// Returns the longest interval between movements that still keeps the
+    /// target from reporting the user as idle.
+    ///
+    /// Values are roughly half of the shortest idle timeout commonly seen for
+    /// each target, leaving room for skipped or auto-paused iterations.
+    pub fn max_interval(&self) -> Duration {
+        match self {
+            // status goes "away" after 5 minutes
+            Target::Teams => Duration::from_secs(150),
+            // status goes "away" after 10 minutes
+            Target::Slack => Duration::from_secs(300),
+            // idle session limits are policy controlled, 2 minutes is a
+            // common lower bound
+            Target::Rdp => Duration::from_secs(60),
+            // shortest screensaver timeout most platforms allow is 1 minute
+            Target::Screensaver => Duration::from_secs(30),
+        }
+    }
+}
+
+impl std::fmt::Display for Target {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = self
+            .to_possible_value()
+            .expect("no target variants should be skipped");
+        write!(f, "{}", name.get_name())
+    }
+}
+
+impl ValueEnum for Target {
+    fn value_variants<'a>() -> &'a [Self] {
+        &[
+            Target::Teams,
+            Target::Slack,
+            Target::Rdp,
+            Target::Screensaver,
+        ]
+    }
+
+    fn to_possible_value(&self) -> Option<PossibleValue> {
+        Some(match self {
+            Target::Teams => PossibleValue::new("teams").help("Microsoft Teams"),
+            Target::Slack => PossibleValue::new("slack").help("Slack"),
+            Target::Rdp => PossibleValue::new("rdp").help("Remote Desktop sessions"),
+            Target::Screensaver => {
+                PossibleValue::new("screensaver").help("OS screensaver and screen lock")
+            }
+        })
+    }
+}
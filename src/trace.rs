@@ -0,0 +1,157 @@
+use crate::config::Config;
+use crate::mouse::{MouseError, MouseExt, PointExt};
+
+use anyhow::{anyhow, bail, ensure, Result};
+use clap::ArgMatches;
+use mouse_rs::Mouse;
+use serde::{Deserialize, Serialize};
+
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+/// Cursor positions captured from real mouse movement.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Trace {
+    /// Frames per second the trace was sampled at.
+    pub fps: u32,
+    pub samples: Vec<TraceSample>,
+}
+
+/// A cursor position, `ms` milliseconds after recording started.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct TraceSample {
+    pub ms: u64,
+    pub x: i32,
+    pub y: i32,
+}
+
+impl Trace {
+    /// Samples the real cursor `fps` times per second for `duration`.
+    pub fn record(fps: u32, duration: Duration) -> Result<Self> {
+        let mouse = Mouse::new();
+        let frame_time = Duration::from_secs_f64(1. / fps as f64);
+        let start = Instant::now();
+        let mut trace = Trace {
+            fps,
+            samples: vec![],
+        };
+
+        while start.elapsed() < duration {
+            let f_start = Instant::now();
+            let pos: PointExt = mouse
+                .get_position()
+                .map_err(|_| anyhow!("failed to get mouse position"))?
+                .into();
+            trace.samples.push(TraceSample {
+                ms: start.elapsed().as_millis() as u64,
+                x: pos.x,
+                y: pos.y,
+            });
+
+            let dt = f_start.elapsed();
+            if dt < frame_time {
+                spin_sleep::sleep(frame_time - dt);
+            }
+        }
+
+        Ok(trace)
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        let file =
+            File::open(path).map_err(|e| anyhow!("failed to open {} ({e})", path.display()))?;
+        let trace: Trace = serde_json::from_reader(BufReader::new(file))
+            .map_err(|e| anyhow!("invalid trace file {} ({e})", path.display()))?;
+        ensure!(
+            !trace.samples.is_empty(),
+            "trace file {} has no samples",
+            path.display()
+        );
+
+        Ok(trace)
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let file =
+            File::create(path).map_err(|e| anyhow!("failed to create {} ({e})", path.display()))?;
+        let mut writer = BufWriter::new(file);
+        serde_json::to_writer(&mut writer, self)
+            .map_err(|e| anyhow!("failed to write trace ({e})"))?;
+        writer.flush()?;
+
+        Ok(())
+    }
+}
+
+/// Runs the `replay` subcommand, moving the cursor as described by
+/// `config`.
+pub fn replay(matches: &ArgMatches, config: &Config) -> Result<()> {
+    let path = matches
+        .get_one::<PathBuf>("FILE")
+        .expect("file should be required by clap");
+    let repeat = matches.get_flag("loop");
+    let max_offset = matches.get_one::<u32>("offset").copied().unwrap_or(0) as i32;
+
+    let trace = Trace::load(path)?;
+    let mouse = MouseExt::with_config(config)?;
+    let rng = config
+        .seed
+        .map_or_else(fastrand::Rng::new, fastrand::Rng::with_seed);
+
+    println!(
+        "Replaying {} samples from {}, press Ctrl-C to stop...",
+        trace.samples.len(),
+        path.display()
+    );
+    loop {
+        // shift each playback a little so loops don't repeat exactly
+        let offset = (
+            rng.i32(-max_offset..=max_offset),
+            rng.i32(-max_offset..=max_offset),
+        );
+        match mouse.play(&trace, offset) {
+            Ok(_) => (),
+            Err(MouseError::Busy) if repeat => {
+                println!(
+                    "mouse in use, pausing for {:.2}s",
+                    config.pause_interval.as_secs_f32()
+                );
+                spin_sleep::sleep(config.pause_interval);
+            }
+            Err(MouseError::Busy) => bail!("stopped replaying, mouse was in use"),
+            Err(e) => bail!("failed to move mouse ({e})"),
+        }
+
+        if !repeat {
+            return Ok(());
+        }
+    }
+}
+
+/// Runs the `record` subcommand, sampling at `fps` unless given on the
+/// command line.
+pub fn run(matches: &ArgMatches, fps: u32) -> Result<()> {
+    let path = matches
+        .get_one::<PathBuf>("output")
+        .expect("output should be required by clap");
+    let duration = *matches
+        .get_one::<Duration>("DURATION")
+        .expect("duration should have a default value");
+    let fps = matches.get_one::<u32>("fps").copied().unwrap_or(fps);
+
+    println!(
+        "Recording cursor for {:.2}s at {fps} fps, use your mouse as you normally would...",
+        duration.as_secs_f32()
+    );
+    let trace = Trace::record(fps, duration)?;
+    trace.save(path)?;
+    println!(
+        "wrote {} samples to {}",
+        trace.samples.len(),
+        path.display()
+    );
+
+    Ok(())
+}
@@ -0,0 +1,526 @@
+use crate::environment::Environment;
+use crate::input;
+use crate::mouse::PointExt;
+
+use anyhow::Result;
+use clap::builder::PossibleValue;
+use clap::ValueEnum;
+use crossterm::cursor::{self, MoveTo, MoveToColumn, MoveToNextLine};
+use crossterm::execute;
+use crossterm::style::{Color, Print, ResetColor, SetForegroundColor, Stylize};
+use crossterm::terminal::{
+    disable_raw_mode, enable_raw_mode, Clear, ClearType, EnterAlternateScreen, LeaveAlternateScreen,
+};
+
+use chrono::NaiveDateTime;
+
+use std::io::{stdout, Stdout, Write};
+use std::time::{Duration, Instant};
+
+/// What the application is currently doing.
+pub enum Status {
+    Ready,
+    StartingIn {
+        remaining: Duration,
+    },
+    Moving {
+        to: PointExt,
+        animate: bool,
+        interval: Duration,
+    },
+    Paused,
+    AutoPausing {
+        remaining: Duration,
+    },
+    SecureDesktop,
+    WaitingForIdle {
+        remaining: Duration,
+    },
+    Dwelling {
+        at: PointExt,
+        duration: Duration,
+    },
+    Drifting {
+        speed: f64,
+    },
+    Watchdog,
+    WaitingForWindow,
+    Locked,
+    LimitReached {
+        limit: Duration,
+    },
+    OffSchedule {
+        resumes: Option<NaiveDateTime>,
+    },
+}
+
+impl Status {
+    /// Returns a short, stable name for this status, suitable for styling
+    /// classes in status bars.
+    fn name(&self) -> &'static str {
+        match self {
+            Status::Ready => "ready",
+            Status::StartingIn { .. } => "starting",
+            Status::Moving { .. } => "moving",
+            Status::Paused => "paused",
+            Status::AutoPausing { .. } => "auto-paused",
+            Status::SecureDesktop => "waiting",
+            Status::WaitingForIdle { .. } => "idle-wait",
+            Status::Dwelling { .. } => "dwelling",
+            Status::Drifting { .. } => "drifting",
+            Status::Watchdog => "watchdog",
+            Status::WaitingForWindow => "window-wait",
+            Status::Locked => "locked",
+            Status::LimitReached { .. } => "limit-reached",
+            Status::OffSchedule { .. } => "off-schedule",
+        }
+    }
+
+    /// Returns a plain text description of this status.
+    ///
+    /// Durations are rounded up to whole seconds so status bars are only
+    /// updated when the displayed text actually changes.
+    fn text(&self) -> String {
+        match self {
+            Status::Ready => "ready".into(),
+            Status::StartingIn { remaining } => {
+                format!("starting in {}s", remaining.as_secs_f32().ceil())
+            }
+            Status::Moving {
+                to, animate: true, ..
+            } => format!("moving to {to}"),
+            Status::Moving {
+                to, animate: false, ..
+            } => format!("placed at {to}"),
+            Status::Paused => "paused".into(),
+            Status::AutoPausing { remaining } => {
+                format!("auto-paused ({}s)", remaining.as_secs_f32().ceil())
+            }
+            Status::SecureDesktop => "waiting for secure desktop".into(),
+            Status::WaitingForIdle { remaining } => {
+                format!("waiting for idle ({}s)", remaining.as_secs_f32().ceil())
+            }
+            Status::Dwelling { at, .. } => format!("resting at {at}"),
+            Status::Drifting { speed } => format!("drifting at {speed:.0} px/s"),
+            Status::Watchdog => "paused by watchdog".into(),
+            Status::WaitingForWindow => "waiting for window".into(),
+            Status::Locked => "paused while locked".into(),
+            Status::LimitReached { .. } => "daily limit reached".into(),
+            Status::OffSchedule { resumes: Some(at) } => {
+                format!("off schedule until {}", at.format("%a %H:%M"))
+            }
+            Status::OffSchedule { resumes: None } => "off schedule".into(),
+        }
+    }
+
+    fn color(&self) -> Option<Color> {
+        match self {
+            Status::Ready | Status::StartingIn { .. } | Status::WaitingForIdle { .. } => None,
+            Status::Moving { .. } | Status::Dwelling { .. } | Status::Drifting { .. } => {
+                Some(Color::Cyan)
+            }
+            Status::Paused
+            | Status::AutoPausing { .. }
+            | Status::SecureDesktop
+            | Status::Watchdog
+            | Status::WaitingForWindow
+            | Status::Locked
+            | Status::LimitReached { .. }
+            | Status::OffSchedule { .. } => Some(Color::Yellow),
+        }
+    }
+}
+
+/// Output formats for desktop status bars.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StatusBar {
+    Waybar,
+    Polybar,
+    Xbar,
+}
+
+impl StatusBar {
+    fn render(&self, status: &Status) -> String {
+        let text = status.text();
+        match self {
+            // custom module with `"return-type": "json"`
+            StatusBar::Waybar => serde_json::json!({
+                "text": text,
+                "alt": status.name(),
+                "class": status.name(),
+                "tooltip": format!("mouser: {text}"),
+            })
+            .to_string(),
+            // custom/script module with `tail = true`
+            StatusBar::Polybar => match status.color() {
+                Some(Color::Yellow) => format!("%{{F#e5c07b}}{text}%{{F-}}"),
+                _ => text,
+            },
+            // streaming plugin, each update is separated by `~~~`
+            StatusBar::Xbar => {
+                let color = match status.color() {
+                    Some(Color::Yellow) => " | color=#e5c07b",
+                    _ => "",
+                };
+                format!("~~~\n{text}{color}\n---\nmouser: {}", status.name())
+            }
+        }
+    }
+}
+
+impl ValueEnum for StatusBar {
+    fn value_variants<'a>() -> &'a [Self] {
+        &[StatusBar::Waybar, StatusBar::Polybar, StatusBar::Xbar]
+    }
+
+    fn to_possible_value(&self) -> Option<PossibleValue> {
+        Some(match self {
+            StatusBar::Waybar => PossibleValue::new("waybar").help("JSON lines for waybar"),
+            StatusBar::Polybar => PossibleValue::new("polybar").help("Text lines for polybar"),
+            StatusBar::Xbar => PossibleValue::new("xbar").help("Streaming xbar/SwiftBar plugin"),
+        })
+    }
+}
+
+/// Draws application status, either as a full screen TUI or as a stream of
+/// lines for a desktop status bar.
+pub struct Ui {
+    stdout: Stdout,
+    statusbar: Option<StatusBar>,
+    hidden: bool,
+    last_line: String,
+    deadline: Option<Instant>,
+    bounds_name: Option<String>,
+    environment: Environment,
+}
+
+impl Ui {
+    pub fn new(statusbar: Option<StatusBar>, environment: Environment) -> Self {
+        Self {
+            stdout: stdout(),
+            statusbar,
+            hidden: false,
+            last_line: String::new(),
+            deadline: None,
+            bounds_name: None,
+            environment,
+        }
+    }
+
+    /// Sets when the application will exit, so a countdown can be shown.
+    pub fn set_deadline(&mut self, deadline: Instant) {
+        self.deadline = Some(deadline);
+    }
+
+    /// Sets the name of the active bounds preset, shown with each status.
+    pub fn set_bounds_name(&mut self, name: &str) {
+        self.bounds_name = Some(name.into());
+    }
+
+    /// Returns `true` if the deadline set with [`Ui::set_deadline`] has
+    /// passed.
+    pub fn is_past_deadline(&self) -> bool {
+        self.deadline
+            .is_some_and(|deadline| Instant::now() >= deadline)
+    }
+
+    /// Prepares the terminal for drawing.
+    ///
+    /// Status bars don't have a terminal attached, so key commands are
+    /// disabled entirely in that case, and the environment summary is written
+    /// to stderr instead.
+    pub fn start(&mut self) -> Result<()> {
+        if self.statusbar.is_some() {
+            input::set_interactive(false);
+            for (label, value) in self.environment.lines() {
+                eprintln!("{label}: {value}");
+            }
+            return Ok(());
+        }
+
+        execute!(
+            self.stdout,
+            cursor::Hide,
+            EnterAlternateScreen,
+            Clear(ClearType::All),
+        )
+        .expect("should be able to execute crossterm commands");
+        enable_raw_mode().expect("should be able to start raw mode");
+
+        self.print_header()?;
+        self.show(Status::Ready)
+    }
+
+    /// Restores the terminal to its original state.
+    pub fn finish(&mut self) {
+        if self.statusbar.is_some() {
+            return;
+        }
+
+        disable_raw_mode().expect("should be able to disable raw mode");
+        execute!(self.stdout, cursor::Show, LeaveAlternateScreen)
+            .expect("should be able to leave alternate screen");
+    }
+
+    /// Hides or shows the TUI.
+    ///
+    /// While hidden the screen is left blank and no status is drawn, but the
+    /// jiggler keeps running. Showing the TUI again redraws the header.
+    pub fn toggle_hidden(&mut self) -> Result<()> {
+        if self.statusbar.is_some() {
+            return Ok(());
+        }
+
+        execute!(self.stdout, Clear(ClearType::All), MoveTo(0, 0))?;
+        self.hidden = !self.hidden;
+        if !self.hidden {
+            self.print_header()?;
+            self.show(Status::Ready)?;
+        }
+
+        Ok(())
+    }
+
+    pub fn show(&mut self, status: Status) -> Result<()> {
+        if let Some(statusbar) = self.statusbar {
+            let line = statusbar.render(&status);
+            if line != self.last_line {
+                writeln!(self.stdout, "{line}")?;
+                self.stdout.flush()?;
+                self.last_line = line;
+            }
+            return Ok(());
+        }
+
+        if self.hidden {
+            return Ok(());
+        }
+
+        execute!(
+            self.stdout,
+            Clear(ClearType::CurrentLine),
+            Print("Status:".bold().dim())
+        )?;
+        match status {
+            Status::Ready => (),
+            Status::StartingIn { remaining } => execute!(
+                self.stdout,
+                Print(" starting in ".dim()),
+                Print(format!("{:.0}s", remaining.as_secs_f32().ceil()).bold()),
+            )?,
+            Status::Moving {
+                to,
+                animate,
+                interval,
+            } => {
+                let action_text = if animate {
+                    " animating to "
+                } else {
+                    " placed cursor at "
+                };
+                execute!(
+                    self.stdout,
+                    Print(action_text.dim()),
+                    SetForegroundColor(Color::Cyan),
+                    Print(to),
+                    ResetColor,
+                    Print(format!(" (interval {:.2}s)", interval.as_secs_f32()).dim()),
+                )?;
+            }
+            Status::Paused => execute!(
+                self.stdout,
+                SetForegroundColor(Color::Yellow),
+                Print(" paused"),
+                ResetColor,
+                Print(" (press ".dim()),
+                Print("p".bold()),
+                Print(" to unpause)".dim()),
+            )?,
+            Status::AutoPausing { remaining } => execute!(
+                self.stdout,
+                Print(" auto-pausing for ".dim()),
+                SetForegroundColor(Color::Yellow),
+                Print(format!("{:.2}s", remaining.as_secs_f32())),
+                ResetColor,
+            )?,
+            Status::SecureDesktop => execute!(
+                self.stdout,
+                SetForegroundColor(Color::Yellow),
+                Print(" waiting"),
+                ResetColor,
+                Print(" for secure desktop to close".dim()),
+            )?,
+            Status::WaitingForIdle { remaining } => execute!(
+                self.stdout,
+                Print(" waiting for system idle, moving in ".dim()),
+                Print(format!("{:.0}s", remaining.as_secs_f32().ceil()).bold()),
+            )?,
+            Status::Dwelling { at, duration } => execute!(
+                self.stdout,
+                Print(" resting at ".dim()),
+                SetForegroundColor(Color::Cyan),
+                Print(at),
+                ResetColor,
+                Print(format!(" for {:.2}s", duration.as_secs_f32()).dim()),
+            )?,
+            Status::Drifting { speed } => execute!(
+                self.stdout,
+                Print(" drifting at ".dim()),
+                SetForegroundColor(Color::Cyan),
+                Print(format!("{speed:.0} px/s")),
+                ResetColor,
+            )?,
+            Status::Watchdog => execute!(
+                self.stdout,
+                SetForegroundColor(Color::Yellow),
+                Print(" paused"),
+                ResetColor,
+                Print(" by watchdog command".dim()),
+            )?,
+            Status::WaitingForWindow => execute!(
+                self.stdout,
+                SetForegroundColor(Color::Yellow),
+                Print(" waiting"),
+                ResetColor,
+                Print(" for the window to appear".dim()),
+            )?,
+            Status::Locked => execute!(
+                self.stdout,
+                SetForegroundColor(Color::Yellow),
+                Print(" paused"),
+                ResetColor,
+                Print(" while the session is locked".dim()),
+            )?,
+            Status::LimitReached { limit } => execute!(
+                self.stdout,
+                SetForegroundColor(Color::Yellow),
+                Print(" stopped"),
+                ResetColor,
+                Print(
+                    format!(
+                        ", daily limit of {:.2}h reached (resumes tomorrow)",
+                        limit.as_secs_f32() / 3600.
+                    )
+                    .dim()
+                ),
+            )?,
+            Status::OffSchedule { resumes } => {
+                execute!(
+                    self.stdout,
+                    SetForegroundColor(Color::Yellow),
+                    Print(" idle"),
+                    ResetColor,
+                    Print(" outside of active hours".dim()),
+                )?;
+                if let Some(at) = resumes {
+                    execute!(
+                        self.stdout,
+                        Print(", resuming ".dim()),
+                        Print(at.format("%a %H:%M").to_string().bold()),
+                    )?;
+                }
+            }
+        }
+        if let Some(name) = &self.bounds_name {
+            execute!(self.stdout, Print(format!(" [{name}]").dim()))?;
+        }
+        if let Some(deadline) = self.deadline {
+            let remaining = deadline.saturating_duration_since(Instant::now()).as_secs();
+            execute!(
+                self.stdout,
+                Print(
+                    format!(
+                        " (exiting in {}:{:02}:{:02})",
+                        remaining / 3600,
+                        remaining / 60 % 60,
+                        remaining % 60
+                    )
+                    .dim()
+                ),
+            )?;
+        }
+        execute!(self.stdout, MoveToColumn(0))?;
+
+        Ok(())
+    }
+
+    fn print_header(&mut self) -> Result<()> {
+        execute!(
+            self.stdout,
+            MoveTo(0, 0),
+            Print("Application started.".dim()),
+            MoveToNextLine(2),
+            Print("Environment".bold()),
+            MoveToNextLine(1),
+        )?;
+        for (label, value) in self.environment.lines() {
+            execute!(
+                self.stdout,
+                Print(format!("{label}: ").dim()),
+                Print(value),
+                MoveToNextLine(1),
+            )?;
+        }
+        execute!(
+            self.stdout,
+            MoveToNextLine(1),
+            Print("Commands".bold()),
+            MoveToNextLine(1),
+            Print("press ".dim()),
+            Print("q".bold()),
+            Print(" to quit".dim()),
+            MoveToNextLine(1),
+            Print("press ".dim()),
+            Print("p".bold()),
+            Print(" to toggle pause".dim()),
+            MoveToNextLine(1),
+            Print("press ".dim()),
+            Print("a".bold()),
+            Print(" to toggle animations".dim()),
+            MoveToNextLine(1),
+            Print("press ".dim()),
+            Print("j".bold()),
+            Print(" to toggle interval jitter".dim()),
+            MoveToNextLine(1),
+            Print("press ".dim()),
+            Print("b".bold()),
+            Print(" to cycle bounds presets".dim()),
+            MoveToNextLine(1),
+            Print("press ".dim()),
+            Print("o".bold()),
+            Print(" to center relative bounds on the cursor".dim()),
+            MoveToNextLine(1),
+            Print("press ".dim()),
+            Print("+".bold()),
+            Print("/".dim()),
+            Print("-".bold()),
+            Print(" to lengthen/shorten the interval".dim()),
+            MoveToNextLine(1),
+            Print("press ".dim()),
+            Print("h".bold()),
+            Print(" to hide/show this screen".dim()),
+            MoveToNextLine(1),
+            Print("press any other key to skip an iteration".dim()),
+            MoveToNextLine(2),
+        )?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn waybar_renders_json() {
+        let line = StatusBar::Waybar.render(&Status::Drifting { speed: 40. });
+        let json = serde_json::from_str::<serde_json::Value>(&line).unwrap();
+
+        assert_eq!(json["text"], "drifting at 40 px/s");
+        assert_eq!(json["alt"], "drifting");
+        assert_eq!(json["class"], "drifting");
+        assert_eq!(json["tooltip"], "mouser: drifting at 40 px/s");
+        assert!(!line.contains('\n'));
+    }
+}
@@ -0,0 +1,94 @@
+use anyhow::{anyhow, Result};
+
+use std::process::{Child, Command, Stdio};
+use std::time::{Duration, Instant};
+
+/// How long the command may run before the check counts as failed.
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Periodically runs a user command to decide whether movements should be
+/// allowed, based on its exit status.
+///
+/// The command runs in the background, so a slow one doesn't hold up key
+/// commands or the UI while it's being checked.
+pub struct Watchdog {
+    command: String,
+    interval: Duration,
+    last_check: Option<Instant>,
+    last_ok: bool,
+    running: Option<(Child, Instant)>,
+}
+
+impl Watchdog {
+    pub fn new(command: String, interval: Duration) -> Self {
+        Self {
+            command,
+            interval,
+            last_check: None,
+            last_ok: false,
+            running: None,
+        }
+    }
+
+    /// Returns `true` if the command last exited successfully, starting it
+    /// again if the check interval has elapsed, without waiting for it.
+    pub fn is_ok(&mut self) -> Result<bool> {
+        if let Some((child, started)) = self.running.as_mut() {
+            let done = match child.try_wait()? {
+                Some(status) => Some(status.success()),
+                None if started.elapsed() >= TIMEOUT => {
+                    let _ = child.kill();
+                    let _ = child.wait();
+                    Some(false)
+                }
+                None => None,
+            };
+            if let Some(ok) = done {
+                self.running = None;
+                self.last_ok = ok;
+                self.last_check = Some(Instant::now());
+            }
+        } else if self
+            .last_check
+            .is_none_or(|last| last.elapsed() >= self.interval)
+        {
+            self.running = Some((self.spawn()?, Instant::now()));
+        }
+
+        Ok(self.last_ok)
+    }
+
+    fn spawn(&self) -> Result<Child> {
+        shell(&self.command)
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+            .map_err(|e| anyhow!("failed to run watchdog command ({e})"))
+    }
+}
+
+impl Drop for Watchdog {
+    fn drop(&mut self) {
+        if let Some((child, _)) = self.running.as_mut() {
+            let _ = child.kill();
+            let _ = child.wait();
+        }
+    }
+}
+
+/// Returns a command that runs `command` with the platform shell.
+pub fn shell(command: &str) -> Command {
+    let mut cmd = if cfg!(windows) {
+        let mut cmd = Command::new("cmd");
+        cmd.arg("/C");
+        cmd
+    } else {
+        let mut cmd = Command::new("sh");
+        cmd.arg("-c");
+        cmd
+    };
+    cmd.arg(command);
+
+    cmd
+}
@@ -0,0 +1,283 @@
+use crate::bounds::Region;
+
+#[cfg(not(windows))]
+use anyhow::anyhow;
+use anyhow::{ensure, Result};
+use thiserror::Error;
+
+/// No visible window matches a title, e.g., because it was closed.
+#[derive(Debug, Error)]
+#[error("no window matches '{0}'")]
+pub struct NotFound(pub String);
+
+/// Returns the rectangle of the focused window, including its frame.
+#[cfg(windows)]
+pub fn active() -> Result<Region> {
+    use windows_sys::Win32::UI::WindowsAndMessaging::GetForegroundWindow;
+
+    // SAFETY: the window handle is checked before use
+    let window = unsafe { GetForegroundWindow() };
+    ensure!(window != 0, "no window is focused");
+
+    win32::rect(window)
+}
+
+/// Returns the rectangle of the first visible window with `title` in its
+/// title or class name (ignoring case), including its frame.
+///
+/// Minimized windows are skipped, since they have nowhere to move.
+#[cfg(windows)]
+pub fn find(title: &str) -> Result<Option<Region>> {
+    use windows_sys::Win32::UI::WindowsAndMessaging::EnumWindows;
+
+    let mut search = win32::Search {
+        query: title.to_lowercase(),
+        found: None,
+    };
+    // SAFETY: the pointer to `search` outlives the enumeration, which calls
+    // back on this thread before returning
+    unsafe {
+        EnumWindows(
+            Some(win32::visit),
+            &mut search as *mut win32::Search as isize,
+        );
+    }
+
+    search.found.transpose()
+}
+
+#[cfg(windows)]
+mod win32 {
+    use super::rectangle;
+    use crate::bounds::Region;
+
+    use anyhow::{ensure, Result};
+    use windows_sys::Win32::Foundation::{BOOL, HWND, LPARAM, RECT};
+    use windows_sys::Win32::UI::WindowsAndMessaging::{
+        GetClassNameW, GetWindowRect, GetWindowTextW, IsIconic, IsWindowVisible,
+    };
+
+    /// Longest title or class name compared, in UTF-16 code units.
+    const MAX_NAME: usize = 512;
+
+    pub struct Search {
+        pub query: String,
+        pub found: Option<Result<Region>>,
+    }
+
+    /// Callback for `EnumWindows`, which stops at the first matching window.
+    pub unsafe extern "system" fn visit(window: HWND, search: LPARAM) -> BOOL {
+        let search = &mut *(search as *mut Search);
+        if IsWindowVisible(window) == 0 || IsIconic(window) != 0 {
+            return 1;
+        }
+
+        let title = name(window, GetWindowTextW);
+        let class = name(window, GetClassNameW);
+        if !title.contains(&search.query) && !class.contains(&search.query) {
+            return 1;
+        }
+
+        search.found = Some(rect(window));
+        0
+    }
+
+    /// Returns a window's text from `get` (e.g., its title) in lowercase.
+    unsafe fn name(
+        window: HWND,
+        get: unsafe extern "system" fn(HWND, *mut u16, i32) -> i32,
+    ) -> String {
+        let mut buffer = [0u16; MAX_NAME];
+        let len = get(window, buffer.as_mut_ptr(), buffer.len() as i32);
+
+        String::from_utf16_lossy(&buffer[..len.max(0) as usize]).to_lowercase()
+    }
+
+    pub fn rect(window: HWND) -> Result<Region> {
+        // SAFETY: the rectangle is only read if the call succeeded
+        let rect = unsafe {
+            let mut rect: RECT = std::mem::zeroed();
+            ensure!(
+                GetWindowRect(window, &mut rect) != 0,
+                "failed to get the position of the window"
+            );
+            rect
+        };
+
+        rectangle(
+            rect.left,
+            rect.top,
+            rect.right - rect.left,
+            rect.bottom - rect.top,
+        )
+    }
+}
+
+/// Returns the rectangle of the focused window, including its frame.
+///
+/// This asks System Events through `osascript`, which needs accessibility
+/// permissions for the terminal.
+#[cfg(target_os = "macos")]
+pub fn active() -> Result<Region> {
+    let output = command_output(
+        "osascript",
+        &[
+            "-e",
+            "tell application \"System Events\" to get {position, size} of first window of \
+             (first application process whose frontmost is true)",
+        ],
+    )?;
+
+    parse_geometry(&output)
+}
+
+/// Script for [`find`], which prints the position and size of the first
+/// window whose title or application name contains the first argument.
+#[cfg(target_os = "macos")]
+const FIND_SCRIPT: &str = r#"on run argv
+    set query to item 1 of argv
+    tell application "System Events"
+        repeat with proc in (application processes whose visible is true)
+            repeat with win in windows of proc
+                set matched to name of proc contains query
+                try
+                    set matched to matched or name of win contains query
+                end try
+                if matched then
+                    set {x, y} to position of win
+                    set {w, h} to size of win
+                    return (x as text) & ", " & y & ", " & w & ", " & h
+                end if
+            end repeat
+        end repeat
+    end tell
+    return ""
+end run"#;
+
+/// Returns the rectangle of the first visible window with `title` in its
+/// title or application name (ignoring case), including its frame.
+#[cfg(target_os = "macos")]
+pub fn find(title: &str) -> Result<Option<Region>> {
+    let output = command_output("osascript", &["-e", FIND_SCRIPT, title])?;
+    if output.is_empty() {
+        return Ok(None);
+    }
+
+    parse_geometry(&output).map(Some)
+}
+
+/// Parses geometry printed by AppleScript as `x, y, width, height`.
+#[cfg(target_os = "macos")]
+fn parse_geometry(output: &str) -> Result<Region> {
+    let values = output
+        .split(',')
+        .map(|v| v.trim().parse::<i32>())
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|_| anyhow!("unexpected window geometry from osascript: '{output}'"))?;
+    ensure!(
+        values.len() == 4,
+        "unexpected window geometry from osascript: '{output}'"
+    );
+
+    rectangle(values[0], values[1], values[2], values[3])
+}
+
+/// Returns the rectangle of the focused window, excluding its frame.
+///
+/// This needs `xdotool`, so it only works with X11 (or XWayland windows).
+#[cfg(all(not(windows), not(target_os = "macos")))]
+pub fn active() -> Result<Region> {
+    let output = command_output(
+        "xdotool",
+        &["getactivewindow", "getwindowgeometry", "--shell"],
+    )?;
+
+    parse_shell(&output)
+}
+
+/// Returns the rectangle of the first visible window with `title` in its
+/// title or class (ignoring case), excluding its frame.
+///
+/// This needs `xdotool`, so it only works with X11 (or XWayland windows).
+#[cfg(all(not(windows), not(target_os = "macos")))]
+pub fn find(title: &str) -> Result<Option<Region>> {
+    // xdotool matches a regular expression, so keep the title literal
+    let mut pattern = String::with_capacity(title.len());
+    for c in title.chars() {
+        if "\\.^$*+?()[]{}|".contains(c) {
+            pattern.push('\\');
+        }
+        pattern.push(c);
+    }
+    let output = command_output(
+        "xdotool",
+        &[
+            "search",
+            "--onlyvisible",
+            "--limit",
+            "1",
+            "--name",
+            "--class",
+            "--classname",
+            &pattern,
+            "getwindowgeometry",
+            "--shell",
+        ],
+    )?;
+    if output.is_empty() {
+        return Ok(None);
+    }
+
+    parse_shell(&output).map(Some)
+}
+
+/// Parses geometry printed by `xdotool ... --shell` as one `KEY=VALUE` per
+/// line.
+#[cfg(all(not(windows), not(target_os = "macos")))]
+fn parse_shell(output: &str) -> Result<Region> {
+    let value = |key: &str| -> Result<i32> {
+        output
+            .lines()
+            .find_map(|line| line.strip_prefix(key)?.strip_prefix('='))
+            .and_then(|v| v.trim().parse().ok())
+            .ok_or_else(|| anyhow!("xdotool didn't report the window's {key}"))
+    };
+
+    rectangle(value("X")?, value("Y")?, value("WIDTH")?, value("HEIGHT")?)
+}
+
+/// Runs `program` and returns what it printed, failing if it couldn't run or
+/// reported an error.
+///
+/// Exiting unsuccessfully without printing anything (e.g., a search with no
+/// results) gives an empty string.
+#[cfg(not(windows))]
+fn command_output(program: &str, args: &[&str]) -> Result<String> {
+    let output = std::process::Command::new(program)
+        .args(args)
+        .output()
+        .map_err(|e| anyhow!("failed to run {program} ({e})"))?;
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    ensure!(
+        output.status.success() || stderr.trim().is_empty(),
+        "{program} failed: {}",
+        stderr.trim()
+    );
+
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// Returns the region covered by a window at `(x, y)` with the given size.
+fn rectangle(x: i32, y: i32, width: i32, height: i32) -> Result<Region> {
+    ensure!(
+        width > 0 && height > 0,
+        "window has no area ({width}x{height})"
+    );
+
+    Ok(Region {
+        x1: x,
+        y1: y,
+        x2: x + width - 1,
+        y2: y + height - 1,
+    })
+}